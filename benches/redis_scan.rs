@@ -25,7 +25,7 @@ fn rand_insert(size: usize) {
         let _: () = conn
             .set(format!("prefix:{}", rand_str(4)), rand_str(16))
             .unwrap();
-        let _: () = conn.set(format!("{}", rand_str(8)), rand_str(16)).unwrap();
+        let _: () = conn.set(rand_str(8), rand_str(16)).unwrap();
     }
 }
 
@@ -61,25 +61,22 @@ fn get_scan_vec_n(conn: &mut redis::Connection, pattern: &str, count: usize) ->
     keys
 }
 
-fn get_scan_vec(conn: &mut redis::Connection, pattern: &str) -> Vec<String> {
-    get_scan_vec_n(conn, pattern, 1000)
-}
-
 pub fn bench_redis_scan(c: &mut Criterion) {
     let mut conn = gams::connect();
+    let mut gams_conn = gams::Conn::new();
 
     gams::db_drop();
     rand_insert(black_box(5000));
 
     c.bench_function("scan_count", |b| {
         b.iter(|| {
-            let n: i32 = gams::get_scan_count(&mut conn, "prefix:*");
+            let n: i32 = gams_conn.get_scan_count("prefix:*");
             assert_eq!(n, 5000);
         })
     });
     c.bench_function("scan_lua", |b| {
         b.iter(|| {
-            let vec: Vec<_> = gams::get_scan_keys(&mut conn, "prefix:*");
+            let vec: Vec<_> = gams_conn.get_scan_keys("prefix:*");
             assert_eq!(vec.len(), 5000);
         })
     });
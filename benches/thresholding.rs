@@ -0,0 +1,52 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::Rng;
+
+// GC-contents of sliding windows
+fn rand_gcs(len: usize) -> Vec<f32> {
+    let mut rng = rand::thread_rng();
+    (0..len).map(|_| rng.gen_range(0.3..0.5)).collect()
+}
+
+// The previous implementation, recomputes the rolling window at every index
+fn thresholding_naive(data: &[f32], lag: usize, threshold: f32, influence: f32) -> Vec<i32> {
+    let mut signals: Vec<i32> = vec![0; data.len()];
+    let mut filtered_data: Vec<f32> = data.to_owned();
+    let mut avg_filter: Vec<f32> = vec![0.; data.len()];
+    let mut std_filter: Vec<f32> = vec![0.; data.len()];
+
+    avg_filter[lag - 1] = gams::mean(&data[0..lag]);
+    std_filter[lag - 1] = gams::stddev(&data[0..lag]);
+
+    for i in lag..data.len() {
+        if (data[i] - avg_filter[i - 1]).abs() > threshold * std_filter[i - 1] {
+            signals[i] = if data[i] > avg_filter[i - 1] { 1 } else { -1 };
+            filtered_data[i] = influence * data[i] + (1. - influence) * filtered_data[i - 1];
+        } else {
+            signals[i] = 0;
+            filtered_data[i] = data[i];
+        }
+
+        avg_filter[i] = gams::mean(&filtered_data[(i - lag)..i]);
+        std_filter[i] = gams::stddev(&filtered_data[(i - lag)..i]);
+    }
+
+    signals
+}
+
+pub fn bench_thresholding(c: &mut Criterion) {
+    let gcs = rand_gcs(100_000);
+
+    let mut group = c.benchmark_group("thresholding");
+    for lag in [100, 1000] {
+        group.bench_with_input(BenchmarkId::new("naive", lag), &lag, |b, lag| {
+            b.iter(|| thresholding_naive(black_box(&gcs), *lag, 3.0, 1.0))
+        });
+        group.bench_with_input(BenchmarkId::new("rolling", lag), &lag, |b, lag| {
+            b.iter(|| gams::thresholding_algo(black_box(&gcs), *lag, 3.0, 1.0))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_thresholding);
+criterion_main!(benches);
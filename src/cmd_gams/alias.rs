@@ -0,0 +1,99 @@
+use clap::*;
+use std::collections::BTreeSet;
+use std::io::BufRead;
+
+// Create clap subcommand arguments
+pub fn make_subcommand() -> Command {
+    Command::new("alias")
+        .about("Add aliases of chromosome names")
+        .after_help(
+            r###"
+* Each line contains a chr_id stored in gams and its aliases, separated by tabs
+    * `chrI  I` or `I  chrI  NC_001133`, the order of columns doesn't matter
+* Lines of a `chr.sizes` style file, `chrI  230218`, are matched by chromosome lengths
+* Aliases are stored in `top:chr_alias` and consulted by `feature`, `rg`, `peak`,
+  `locate` and `anno`
+
+"###,
+        )
+        .arg(
+            Arg::new("infiles")
+                .required(true)
+                .index(1)
+                .num_args(1..)
+                .help("Set the input files to use"),
+        )
+}
+
+// command implementation
+pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
+    // redis connection
+    let mut conn = gams::Conn::new();
+
+    let len_of = conn.get_chr_len();
+
+    // alias => chr_id
+    let mut alias_of = conn.get_chr_alias();
+    let mut unresolved: BTreeSet<String> = BTreeSet::new();
+
+    for infile in args.get_many::<String>("infiles").unwrap() {
+        let reader = intspan::reader(infile);
+        for line in reader.lines().map_while(Result::ok) {
+            if line.starts_with('#') || line.trim().is_empty() {
+                continue;
+            }
+            let parts: Vec<&str> = line.split('\t').map(|e| e.trim()).collect();
+
+            // chr.sizes style, the second column matches the length of a chromosome
+            let length = if parts.len() == 2 {
+                parts[1]
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|l| len_of.values().any(|v| v == l))
+            } else {
+                None
+            };
+
+            if let Some(length) = length {
+                if len_of.contains_key(parts[0]) {
+                    continue;
+                }
+
+                let chrs: Vec<&String> = len_of
+                    .iter()
+                    .filter(|(_, v)| **v == length)
+                    .map(|(k, _)| k)
+                    .collect();
+                if chrs.len() == 1 {
+                    alias_of.insert(parts[0].to_string(), chrs[0].to_string());
+                } else {
+                    // same lengths, ambiguous
+                    unresolved.insert(parts[0].to_string());
+                }
+            } else {
+                let chr_id = match parts.iter().find(|e| len_of.contains_key(**e)) {
+                    Some(chr_id) => chr_id.to_string(),
+                    None => {
+                        unresolved.insert(parts[0].to_string());
+                        continue;
+                    }
+                };
+
+                for part in &parts {
+                    if part.is_empty() || len_of.contains_key(*part) {
+                        continue;
+                    }
+                    alias_of.insert(part.to_string(), chr_id.clone());
+                }
+            }
+        }
+    }
+    gams::report_unresolved(&unresolved);
+
+    let json = serde_json::to_string(&alias_of).unwrap();
+    conn.insert_str("top:chr_alias", &json);
+
+    eprintln!("There are {} aliases in the database", alias_of.len());
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use clap::*;
 use intspan::{IntSpan, Range};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::ffi::OsStr;
 use std::io::BufRead;
 use std::path::Path;
@@ -13,7 +13,9 @@ pub fn make_subcommand() -> Command {
             r###"
 * This command is a simplified and accelerated version of `rgr prop`
 * Lines without a valid ctg_id and a valid range will not be output
+* Lines with ctgs not in the database get an empty prop, and these ctgs are reported
 * If `--header` is set, the appended field name will be `prefixProp`
+* Chromosome names in the runlist file are resolved via aliases stored by `gams alias`
 
 "###,
         )
@@ -83,12 +85,24 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
 
     let set = {
         let json = intspan::read_json(args.get_one::<String>("runlist").unwrap());
-        intspan::json2set(&json)
+        let alias_of = conn.get_chr_alias();
+        let chrs: BTreeSet<String> = conn.get_vec_chr().into_iter().collect();
+        gams::resolve_set(&intspan::json2set(&json), &alias_of, &chrs)
     };
 
+    // IntSpans of ctgs
+    let parent_of: HashMap<String, IntSpan> = conn
+        .get_bundle_ctg(None)
+        .into_values()
+        .map(|ctg| (ctg.id, IntSpan::from_pair(ctg.chr_start, ctg.chr_end)))
+        .collect();
+
     // local caches of the feature IntSpan for each ctg
     let mut cache: HashMap<String, IntSpan> = HashMap::new();
 
+    // ctgs not in the database
+    let mut unknown: BTreeSet<String> = BTreeSet::new();
+
     //----------------------------
     // Operating
     //----------------------------
@@ -118,28 +132,30 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
                 None => continue,
             }
             .to_string();
+            let parent = match parent_of.get(&ctg_id) {
+                Some(parent) => parent,
+                None => {
+                    writer.write_fmt(format_args!("{}\t\n", line))?;
+                    unknown.insert(ctg_id);
+                    continue 'LINE;
+                }
+            };
 
             let range = Range::from_str(parts.get(idx_range - 1).unwrap());
             if !range.is_valid() {
                 continue 'LINE;
             }
-            let intspan = range.intspan();
-
-            let mut prop = 0.0;
-            if set.contains_key(range.chr()) {
-                if !cache.contains_key(&ctg_id) {
-                    let (_, chr_start, chr_end) = conn.get_ctg_pos(&ctg_id);
-                    let ctg_intspan = IntSpan::from_pair(chr_start, chr_end);
-                    let parent = set.get(range.chr()).unwrap().intersect(&ctg_intspan);
-                    cache.insert(ctg_id.clone(), parent);
-                }
 
-                let intxn = cache.get(&ctg_id).unwrap().intersect(&intspan);
-                prop = intxn.cardinality() as f32 / intspan.cardinality() as f32;
-            }
+            let prop = gams::cache_prop(&set, &ctg_id, parent, &range, &mut cache);
             writer.write_fmt(format_args!("{}\t{:.4}\n", line, prop))?;
         }
     }
+    if !unknown.is_empty() {
+        eprintln!(
+            "Unknown ctgs: {}",
+            unknown.into_iter().collect::<Vec<_>>().join(", ")
+        );
+    }
 
     Ok(())
 }
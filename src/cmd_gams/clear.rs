@@ -18,6 +18,12 @@ List of actions:
 * peak
     * peak:*
     * cnt:peak:*
+* sw
+    * sw:*
+* wave
+    * wave:*
+* alias
+    * top:chr_alias
 
 "###,
         )
@@ -73,6 +79,27 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
                     clear_lua("cnt:peak:*");
                 }
             }
+            "sw" => {
+                if is_iter {
+                    clear_iter("sw:*");
+                } else {
+                    clear_lua("sw:*");
+                }
+            }
+            "wave" => {
+                if is_iter {
+                    clear_iter("wave:*");
+                } else {
+                    clear_lua("wave:*");
+                }
+            }
+            "alias" => {
+                if is_iter {
+                    clear_iter("top:chr_alias");
+                } else {
+                    clear_lua("top:chr_alias");
+                }
+            }
             _ => unreachable!(),
         };
     }
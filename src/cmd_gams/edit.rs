@@ -0,0 +1,224 @@
+use clap::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+// Create clap subcommand arguments
+pub fn make_subcommand() -> Command {
+    Command::new("edit")
+        .about("Delete, deduplicate or merge stored features")
+        .after_help(
+            r###"
+List of actions:
+
+* delete
+    * Delete features selected by --tag, --range and --id
+    * At least one of them is needed
+* dedup
+    * Features of the same type with identical ranges are reduced to the first one, across
+      imports with different --tag
+    * --by-tag only reduces features of the same tag
+    * Features of different types, e.g. a gene and its mRNA, are never reduced
+* merge
+    * Overlapping features of the same tag and type are merged into one record
+
+* Filters are combined, e.g. `--tag spo11 --range I` selects spo11 features on chromosome I
+* dedup and merge only touch selected features, all features when no filters are given
+* --range accepts chromosome names, e.g. `I`, and ranges, e.g. `I:1-10000`
+* Other features keep their ids, and `cnt:feature:{ctg_id}` is untouched
+* Stored windows of removed or merged features, `sw:{feature_id}:*`, are deleted
+
+"###,
+        )
+        .arg(
+            Arg::new("action")
+                .required(true)
+                .index(1)
+                .num_args(1)
+                .value_parser([
+                    builder::PossibleValue::new("delete"),
+                    builder::PossibleValue::new("dedup"),
+                    builder::PossibleValue::new("merge"),
+                ])
+                .help("What to do"),
+        )
+        .arg(
+            Arg::new("tag")
+                .long("tag")
+                .short('t')
+                .num_args(1)
+                .action(ArgAction::Append)
+                .help("Features with these tags"),
+        )
+        .arg(
+            Arg::new("range")
+                .long("range")
+                .short('r')
+                .num_args(1)
+                .action(ArgAction::Append)
+                .help("Features overlapping these chromosomes or ranges"),
+        )
+        .arg(
+            Arg::new("id")
+                .long("id")
+                .num_args(1)
+                .action(ArgAction::Append)
+                .help("Features with these ids"),
+        )
+        .arg(
+            Arg::new("by-tag")
+                .long("by-tag")
+                .action(ArgAction::SetTrue)
+                .help("Only dedup features of the same tag"),
+        )
+        .arg(
+            Arg::new("size")
+                .long("size")
+                .num_args(1)
+                .default_value("100")
+                .value_parser(value_parser!(usize))
+                .help("Batch size for one Redis submission"),
+        )
+}
+
+// command implementation
+pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
+    // opts
+    let opt_action = args.get_one::<String>("action").unwrap().as_str();
+    let opt_size = *args.get_one::<usize>("size").unwrap();
+    let is_by_tag = args.get_flag("by-tag");
+
+    let tags: Option<BTreeSet<String>> = args
+        .get_many::<String>("tag")
+        .map(|v| v.map(|e| e.to_string()).collect());
+    let ids: Option<BTreeSet<String>> = args
+        .get_many::<String>("id")
+        .map(|v| v.map(|e| e.to_string()).collect());
+
+    if opt_action == "delete" && tags.is_none() && ids.is_none() && !args.contains_id("range") {
+        anyhow::bail!("delete needs at least one of --tag, --range and --id");
+    }
+
+    // redis connection
+    let mut conn = gams::Conn::with_size(opt_size);
+
+    // chr_id => IntSpan
+    let regions: Option<BTreeMap<String, intspan::IntSpan>> =
+        args.get_many::<String>("range").map(|v| {
+            let alias_of = conn.get_chr_alias();
+            let len_of = conn.get_chr_len();
+            to_regions(v, &alias_of, &len_of)
+        });
+
+    let is_selected = |f: &gams::Feature| {
+        let rg = intspan::Range::from_str(&f.range);
+        tags.as_ref().is_none_or(|t| t.contains(&f.tag))
+            && ids.as_ref().is_none_or(|i| i.contains(&f.id))
+            && regions.as_ref().is_none_or(|r| {
+                r.get(rg.chr()).is_some_and(|ints| {
+                    !ints
+                        .intersect(&intspan::IntSpan::from_pair(rg.start, rg.end))
+                        .is_empty()
+                })
+            })
+    };
+
+    let ctg_of = conn.get_bundle_ctg(None);
+
+    let mut n_removed = 0;
+    for ctg_id in ctg_of.keys() {
+        // All features in this ctg, in the order of serials
+        let jsons: Vec<String> = conn.get_scan_values(&format!("feature:{}:*", ctg_id));
+        let mut features: Vec<gams::Feature> = jsons
+            .iter()
+            .map(|el| serde_json::from_str(el).unwrap())
+            .collect();
+        features.sort_by_key(|f| gams::extract_serial(&f.id));
+
+        let (selected, mut kept): (Vec<gams::Feature>, Vec<gams::Feature>) =
+            features.iter().cloned().partition(|f| is_selected(f));
+        if selected.is_empty() {
+            continue;
+        }
+
+        match opt_action {
+            "dedup" => kept.extend(gams::dedup_features(&selected, is_by_tag)),
+            "merge" => kept.extend(gams::merge_features(&selected)),
+            _ => {}
+        }
+        if kept.len() == features.len() {
+            continue;
+        }
+        let n_kept = kept.len();
+        eprintln!("{}: {} => {} features", ctg_id, features.len(), n_kept);
+        n_removed += features.len() - n_kept;
+
+        // ids are kept, merged features take the ids of their first members
+        let kept_ids: BTreeSet<String> = kept.iter().map(|f| f.id.clone()).collect();
+        let removed_ids: Vec<String> = features
+            .iter()
+            .filter(|f| !kept_ids.contains(&f.id))
+            .map(|f| f.id.clone())
+            .collect();
+        conn.del_keys(&removed_ids);
+
+        let old_of: BTreeMap<&String, &gams::Feature> =
+            features.iter().map(|f| (&f.id, f)).collect();
+        let mut stale_ids: BTreeSet<String> = removed_ids.into_iter().collect();
+        for feature in kept.iter().filter(|f| old_of.get(&f.id) != Some(f)) {
+            let json = serde_json::to_string(feature).unwrap();
+            conn.pipe_add(&feature.id, &json);
+            stale_ids.insert(feature.id.clone());
+        }
+        conn.pipe_submit();
+
+        // windows of removed or merged features
+        let sw_keys: Vec<String> = conn
+            .get_scan_keys(&format!("sw:feature:{}:*", ctg_id))
+            .into_iter()
+            .filter(|k| {
+                k.strip_prefix("sw:")
+                    .and_then(|e| e.rsplit_once(':'))
+                    .is_some_and(|(target_id, _)| stale_ids.contains(target_id))
+            })
+            .collect();
+        conn.del_keys(&sw_keys);
+    }
+
+    eprintln!("Remove {} features", n_removed);
+    let n_feature = conn.get_scan_count("feature:*");
+    eprintln!("There are {} features in the database", n_feature);
+
+    Ok(())
+}
+
+/// Chromosome names and ranges, merged by chromosomes
+fn to_regions<'a>(
+    rgs: impl Iterator<Item = &'a String>,
+    alias_of: &BTreeMap<String, String>,
+    len_of: &BTreeMap<String, usize>,
+) -> BTreeMap<String, intspan::IntSpan> {
+    let mut regions: BTreeMap<String, intspan::IntSpan> = BTreeMap::new();
+    let mut unresolved: BTreeSet<String> = BTreeSet::new();
+
+    for rg in rgs {
+        let mut range = if rg.contains(':') {
+            intspan::Range::from_str(rg)
+        } else {
+            let chr_id = alias_of.get(rg).unwrap_or(rg);
+            let len = *len_of.get(chr_id).unwrap_or(&0) as i32;
+            intspan::Range::from(chr_id, 1, len.max(1))
+        };
+        gams::resolve_chr(alias_of, &mut range);
+        if !len_of.contains_key(range.chr()) {
+            unresolved.insert(rg.to_string());
+            continue;
+        }
+
+        regions
+            .entry(range.chr().to_string())
+            .or_default()
+            .add_pair(range.start, range.end);
+    }
+    gams::report_unresolved(&unresolved);
+
+    regions
+}
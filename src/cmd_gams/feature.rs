@@ -1,5 +1,5 @@
 use clap::*;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 // Create clap subcommand arguments
 pub fn make_subcommand() -> Command {
@@ -9,6 +9,13 @@ pub fn make_subcommand() -> Command {
             r###"
 Please process multiple files separately, as you will have to tag each file
 
+* --format auto detects .bed, .gff/.gff3/.gtf and .vcf files by extensions, optionally gzipped
+    * Others are treated as range files, e.g. `I:100-200`
+
+* GFF3/GTF files are imported as gene models
+    * One feature per record of --type
+    * Strand, feature type, Name, biotype, ID and Parent are kept in the record
+
 "###,
         )
         .arg(
@@ -25,6 +32,7 @@ Please process multiple files separately, as you will have to tag each file
                 .value_parser(value_parser!(usize))
                 .help("Batch size for one Redis submission"),
         )
+        .arg(gams::format_arg())
         .arg(
             Arg::new("tag")
                 .long("tag")
@@ -33,6 +41,22 @@ Please process multiple files separately, as you will have to tag each file
                 .default_value("feature")
                 .help("Feature tags"),
         )
+        .arg(
+            Arg::new("type")
+                .long("type")
+                .num_args(1)
+                .action(ArgAction::Append)
+                .default_values([
+                    "gene",
+                    "mRNA",
+                    "transcript",
+                    "exon",
+                    "CDS",
+                    "five_prime_UTR",
+                    "three_prime_UTR",
+                ])
+                .help("Feature types to be imported from GFF3/GTF files"),
+        )
 }
 
 // command implementation
@@ -41,38 +65,66 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     let infile = args.get_one::<String>("infile").unwrap();
     let opt_tag = args.get_one::<String>("tag").unwrap().as_str();
     let opt_size = *args.get_one::<usize>("size").unwrap();
+    let opt_format = args.get_one::<String>("format").unwrap().as_str();
 
     // redis connection
     let mut conn = gams::Conn::with_size(opt_size);
 
-    // ctg_id => [Range]
+    // ctg_id => [Feature]
     // act as a sorter
-    let ranges_of = {
+    let features_of = {
         // index of ctgs
         let lapper_of = conn.get_idx_ctg();
-        gams::read_range(infile, &lapper_of)
+        let alias_of = conn.get_chr_alias();
+
+        if gams::detect_format(infile, opt_format) == "gff" {
+            let types: BTreeSet<String> = args
+                .get_many::<String>("type")
+                .unwrap()
+                .map(|e| e.to_string())
+                .collect();
+            gams::read_gff(infile, &types, opt_tag, &lapper_of, &alias_of)
+        } else {
+            let ranges_of = gams::read_range(infile, opt_format, &lapper_of, &alias_of);
+
+            let mut features_of: BTreeMap<String, Vec<gams::Feature>> = BTreeMap::new();
+            for (ctg_id, rg) in gams::ctg_range_tuple(&ranges_of) {
+                let feature = gams::Feature {
+                    range: rg.to_string(),
+                    length: rg.end() - rg.start() + 1,
+                    tag: opt_tag.to_string(),
+                    strand: Some(rg.strand().to_string()).filter(|e| !e.is_empty()),
+                    ..Default::default()
+                };
+                features_of.entry(ctg_id).or_default().push(feature);
+            }
+            features_of
+        }
     };
 
-    // (ctg_id, Range)
-    let ctg_ranges = gams::ctg_range_tuple(&ranges_of);
+    // (ctg_id, Feature)
+    let ctg_features: Vec<(&String, &gams::Feature)> = features_of
+        .iter()
+        .flat_map(|(k, v)| v.iter().map(move |f| (k, f)))
+        .collect();
 
-    // total number of ranges
-    eprintln!("There are {} features in this file", ctg_ranges.len());
+    // total number of features
+    eprintln!("There are {} features in this file", ctg_features.len());
 
     // start serial of each ctg
     // To minimize expensive Redis operations, locally increment the serial number
     // For each ctg, we increase the counter in Redis only once
     let mut serial_of: BTreeMap<String, i32> = BTreeMap::new();
 
-    for (i, (ctg_id, rg)) in ctg_ranges.iter().enumerate() {
+    for (i, (ctg_id, feature)) in ctg_features.iter().enumerate() {
         // prompts
         if i > 1 && i % (opt_size * 10) == 0 {
             eprintln!("Insert {} records", i);
         }
 
         // serial and id
-        if !serial_of.contains_key(ctg_id) {
-            let cnt = ranges_of.get(ctg_id).unwrap().len() as i32;
+        if !serial_of.contains_key(*ctg_id) {
+            let cnt = features_of.get(*ctg_id).unwrap().len() as i32;
             // Redis counter
             // increase serial by cnt
             let serial = conn.incr_sn_n(&format!("cnt:feature:{ctg_id}"), cnt);
@@ -81,15 +133,13 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
             serial_of.insert(ctg_id.to_string(), serial - cnt);
         }
 
-        let serial = serial_of.get_mut(ctg_id).unwrap();
+        let serial = serial_of.get_mut(*ctg_id).unwrap();
         *serial += 1;
         let feature_id = format!("feature:{ctg_id}:{serial}");
 
         let feature = gams::Feature {
             id: feature_id.clone(),
-            range: rg.to_string(),
-            length: rg.end() - rg.start() + 1,
-            tag: opt_tag.to_string(),
+            ..(*feature).clone()
         };
 
         let json = serde_json::to_string(&feature).unwrap();
@@ -8,6 +8,13 @@ pub fn make_subcommand() -> Command {
         .about("Generate the database from (gzipped) fasta files")
         .after_help(
             r###"
+* --split controls where a valid region is cut into pieces
+    * piece: cut at every --piece bp
+    * gap: cut at the nearest filled gap or soft-masked (lowercase) stretch
+    * runlist: cut at the nearest region of --runlist, e.g. intergenic.json
+    * Cut points are searched within half a --piece around the fixed one,
+      and fall back to it if no candidates are found
+
 "###,
         )
         .arg(
@@ -48,6 +55,25 @@ pub fn make_subcommand() -> Command {
                 .value_parser(value_parser!(i32))
                 .help("Skip pieces smaller than this"),
         )
+        .arg(
+            Arg::new("split")
+                .long("split")
+                .num_args(1)
+                .value_parser([
+                    builder::PossibleValue::new("piece"),
+                    builder::PossibleValue::new("gap"),
+                    builder::PossibleValue::new("runlist"),
+                ])
+                .default_value("piece")
+                .help("Strategy of splitting"),
+        )
+        .arg(
+            Arg::new("runlist")
+                .long("runlist")
+                .num_args(1)
+                .required_if_eq("split", "runlist")
+                .help("Cut within regions of this runlist file"),
+        )
 }
 
 // command implementation
@@ -57,6 +83,12 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     let opt_piece = *args.get_one::<i32>("piece").unwrap();
     let opt_fill = *args.get_one::<i32>("fill").unwrap();
     let opt_min = *args.get_one::<i32>("min").unwrap();
+    let opt_split = args.get_one::<String>("split").unwrap().as_str();
+
+    let runlist_set = match args.get_one::<String>("runlist") {
+        Some(runlist) => intspan::json2set(&intspan::read_json(runlist)),
+        None => BTreeMap::new(),
+    };
 
     // redis connection
     let mut conn = gams::Conn::new();
@@ -104,15 +136,31 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
                 valid_set = valid_set.excise(opt_min);
                 eprintln!("Valid region for {}:\n{}\n", chr_id, valid_set.runlist());
 
+                // Preferred cut points
+                let boundary = match opt_split {
+                    "gap" => ambiguous_set.union(&masked_set(chr_seq)),
+                    "runlist" => runlist_set.get(chr_id).cloned().unwrap_or_default(),
+                    _ => intspan::IntSpan::new(),
+                };
+
                 let valid_ranges = valid_set.ranges();
                 for i in 0..valid_set.span_size() {
                     let mut cur_regions = vec![];
                     let mut pos = *valid_ranges.get(i * 2).unwrap();
                     let max = *valid_ranges.get(i * 2 + 1).unwrap();
                     while max - pos + 1 > opt_piece {
+                        let target = pos + opt_piece - 1;
+                        let end = gams::nearest_cut(
+                            &boundary,
+                            target,
+                            target - opt_piece / 2,
+                            (target + opt_piece / 2).min(max - 1),
+                        )
+                        .unwrap_or(target);
+
                         cur_regions.push(pos);
-                        cur_regions.push(pos + opt_piece - 1);
-                        pos += opt_piece;
+                        cur_regions.push(end);
+                        pos = end + 1;
                     }
 
                     if cur_regions.is_empty() {
@@ -192,3 +240,25 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Soft-masked (lowercase) regions of a sequence
+fn masked_set(seq: &[u8]) -> intspan::IntSpan {
+    let mut masked_set = intspan::IntSpan::new();
+
+    let mut start: Option<i32> = None;
+    for (i, item) in seq.iter().enumerate() {
+        if item.is_ascii_lowercase() {
+            if start.is_none() {
+                start = Some(i as i32 + 1);
+            }
+        } else if let Some(s) = start {
+            masked_set.add_pair(s, i as i32);
+            start = None;
+        }
+    }
+    if let Some(s) = start {
+        masked_set.add_pair(s, seq.len() as i32);
+    }
+
+    masked_set
+}
@@ -1,7 +1,7 @@
 use clap::*;
+use itertools::Itertools;
 use rust_lapper::Lapper;
-use std::collections::BTreeMap;
-use std::io::BufRead;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 // Create clap subcommand arguments
 pub fn make_subcommand() -> Command {
@@ -12,6 +12,17 @@ pub fn make_subcommand() -> Command {
 * `--seq` might not be useful, just in case that you can't access the fasta files
 * To use `--count`, `gams rg` should have inserted .rg files
 * `--seq` will unset `--count`
+* `--agg` aggregates values of rgs, see `gams rg --value`, instead of counting them
+    * sum, mean, max, wmean (weighted by overlapping lengths)
+    * Ranges without valued rgs are reported with empty values
+* With `--tag`, rgs of each tag are counted separately, one column per tag
+* `--seq` stitches sequences across ctgs, positions not in any ctg are filled with `N`
+* `--seq` is strand-aware, ranges like `I(-):100-200` are reverse complemented
+    * `--up` and `--down` are relative to the strand
+    * Headers of flanked sequences are the extended ranges
+* With `--all`, every overlapping ctg is reported along with the clipped sub-range
+    * `range  ctg_id  sub-range`, with an extra count column with `--count`
+* With `--file`, --format auto detects .bed, .gff/.gff3/.gtf and .vcf files by extensions
 * This subcommand is mainly used as a test program
 
 "###,
@@ -29,6 +40,7 @@ pub fn make_subcommand() -> Command {
                 .action(ArgAction::SetTrue)
                 .help("Treat ranges as filenames"),
         )
+        .arg(gams::format_arg())
         .arg(
             Arg::new("rebuild")
                 .long("rebuild")
@@ -43,6 +55,37 @@ pub fn make_subcommand() -> Command {
                 .action(ArgAction::SetTrue)
                 .help("Extracts sequences defined by the range(s)"),
         )
+        .arg(
+            Arg::new("up")
+                .long("up")
+                .num_args(1)
+                .default_value("0")
+                .value_parser(value_parser!(i32))
+                .help("Upstream flanking bases of --seq"),
+        )
+        .arg(
+            Arg::new("down")
+                .long("down")
+                .num_args(1)
+                .default_value("0")
+                .value_parser(value_parser!(i32))
+                .help("Downstream flanking bases of --seq"),
+        )
+        .arg(
+            Arg::new("line")
+                .long("line")
+                .num_args(1)
+                .default_value("0")
+                .value_parser(value_parser!(usize))
+                .help("Sequence line length of --seq, 0 for no wrapping"),
+        )
+        .arg(
+            Arg::new("all")
+                .long("all")
+                .short('a')
+                .action(ArgAction::SetTrue)
+                .help("Report all overlapping ctgs, not just the first one"),
+        )
         .arg(
             Arg::new("count")
                 .long("count")
@@ -50,6 +93,28 @@ pub fn make_subcommand() -> Command {
                 .action(ArgAction::SetTrue)
                 .help("Count overlaps with the ranges stored in gams"),
         )
+        .arg(
+            Arg::new("agg")
+                .long("agg")
+                .num_args(1)
+                .value_parser([
+                    builder::PossibleValue::new("count"),
+                    builder::PossibleValue::new("sum"),
+                    builder::PossibleValue::new("mean"),
+                    builder::PossibleValue::new("max"),
+                    builder::PossibleValue::new("wmean"),
+                ])
+                .default_value("count")
+                .help("Aggregation of --count"),
+        )
+        .arg(
+            Arg::new("tag")
+                .long("tag")
+                .short('t')
+                .num_args(1)
+                .action(ArgAction::Append)
+                .help("Count rgs of these tags, see `gams rg --tag`"),
+        )
         .arg(
             Arg::new("outfile")
                 .long("outfile")
@@ -66,12 +131,19 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
 
     let is_rebuild = args.get_flag("rebuild");
     let is_file = args.get_flag("file");
+    let opt_format = args.get_one::<String>("format").unwrap().as_str();
     let is_seq = args.get_flag("seq");
+    let is_all = args.get_flag("all");
+    let opt_up = *args.get_one::<i32>("up").unwrap();
+    let opt_down = *args.get_one::<i32>("down").unwrap();
+    let opt_line = *args.get_one::<usize>("line").unwrap();
+    let is_flank = is_seq && (opt_up > 0 || opt_down > 0);
     let is_count = if is_seq {
         false
     } else {
         args.get_flag("count")
     };
+    let opt_agg = args.get_one::<String>("agg").unwrap().as_str();
 
     // redis connection
     let mut conn = gams::Conn::new();
@@ -85,10 +157,8 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     let mut rgs: Vec<String> = vec![];
     if is_file {
         for infile in args.get_many::<String>("ranges").unwrap() {
-            let reader = intspan::reader(infile);
-            for line in reader.lines().map_while(Result::ok) {
-                let parts: Vec<&str> = line.split('\t').collect();
-                rgs.push(parts.first().unwrap().to_string());
+            for range in gams::read_ranges(infile, opt_format) {
+                rgs.push(range.to_string());
             }
         }
     } else {
@@ -101,44 +171,175 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
 
     // index of ctgs
     let lapper_ctg_of = conn.get_idx_ctg();
-    let lapper_rg_of: BTreeMap<String, Lapper<u32, String>> = if is_count {
-        conn.get_idx_rg()
-    } else {
-        BTreeMap::new()
-    };
+    let alias_of = conn.get_chr_alias();
+    let len_of = conn.get_chr_len();
+    // one index for each tag, or one for all rgs
+    let mut lapper_rgs: Vec<BTreeMap<String, Lapper<u32, String>>> = vec![];
+    if is_count {
+        match args.get_many::<String>("tag") {
+            Some(tags) => {
+                for tag in tags {
+                    lapper_rgs.push(conn.get_idx_rg(Some(tag)));
+                }
+            }
+            None => lapper_rgs.push(conn.get_idx_rg(None)),
+        }
+    }
+
+    // local caches of ctg sequences
+    let mut seq_of: HashMap<String, String> = HashMap::new();
 
     // processing each range
+    let mut unresolved: BTreeSet<String> = BTreeSet::new();
     for rg in &rgs {
         let mut range = intspan::Range::from_str(rg);
         if !range.is_valid() {
             continue;
         }
+        let strand = range.strand().to_string();
         *range.strand_mut() = "".to_string();
+        gams::resolve_chr(&alias_of, &mut range);
+        if !lapper_ctg_of.contains_key(range.chr()) {
+            unresolved.insert(range.chr().to_string());
+            continue;
+        }
 
-        let ctg_id = gams::find_one_idx(&lapper_ctg_of, &range);
+        if is_flank {
+            let (left, right) = if strand == "-" {
+                (opt_down, opt_up)
+            } else {
+                (opt_up, opt_down)
+            };
+            range.start = (range.start - left).max(1);
+            range.end += right;
+        }
 
-        if ctg_id.is_empty() {
+        // (ctg_id, clipped sub-range)
+        let subs = gams::find_all_idx(&lapper_ctg_of, &range);
+        if subs.is_empty() {
             continue;
         }
 
         if is_seq {
-            let ctg = conn.get_ctg(&ctg_id);
-            let chr_start = ctg.chr_start;
+            // not beyond the end of the chromosome
+            let chr_len = *len_of.get(range.chr()).unwrap() as i32;
+            range.end = range.end.min(chr_len);
 
-            let ctg_start = (range.start() - chr_start + 1) as usize;
-            let ctg_end = (range.end() - chr_start + 1) as usize;
+            let mut seq = stitch_seq(&mut conn, &mut seq_of, &range, &subs);
+            if strand == "-" {
+                seq = String::from_utf8(bio::alphabets::dna::revcomp(seq.bytes())).unwrap();
+            }
 
-            let ctg_seq = conn.get_seq(&ctg_id);
-            // from <= x < to, zero-based
-            let seq = ctg_seq.get((ctg_start - 1)..(ctg_end)).unwrap();
-            writer.write_fmt(format_args!(">{}\n{}\n", rg, seq))?;
+            let header = if is_flank {
+                *range.strand_mut() = strand;
+                range.to_string()
+            } else {
+                rg.to_string()
+            };
+            writer.write_fmt(format_args!(">{}\n{}", header, wrap_seq(&seq, opt_line)))?;
+        } else if is_all {
+            for (ctg_id, sub) in &subs {
+                if is_count {
+                    let cnt = lapper_rgs
+                        .iter()
+                        .map(|lapper_rg_of| count_field(lapper_rg_of, &[(ctg_id, sub)], opt_agg))
+                        .join("\t");
+                    writer.write_fmt(format_args!("{}\t{}\t{}\t{}\n", rg, ctg_id, sub, cnt))?;
+                } else {
+                    writer.write_fmt(format_args!("{}\t{}\t{}\n", rg, ctg_id, sub))?;
+                }
+            }
         } else if is_count {
-            let cnt = gams::count_rg(&lapper_rg_of, &ctg_id, &range);
+            // each rg was stored in only one ctg
+            let parts: Vec<(&String, &intspan::Range)> =
+                subs.iter().map(|(ctg_id, _)| (ctg_id, &range)).collect();
+            let cnt = lapper_rgs
+                .iter()
+                .map(|lapper_rg_of| count_field(lapper_rg_of, &parts, opt_agg))
+                .join("\t");
             writer.write_fmt(format_args!("{}\t{}\n", rg, cnt))?;
         } else {
-            writer.write_fmt(format_args!("{}\t{}\n", rg, ctg_id))?;
+            writer.write_fmt(format_args!("{}\t{}\n", rg, subs.first().unwrap().0))?;
         }
     }
+    gams::report_unresolved(&unresolved);
 
     Ok(())
 }
+
+/// Sequences of the sub-ranges are joined, and gaps between ctgs are filled with `N`
+fn stitch_seq(
+    conn: &mut gams::Conn,
+    seq_of: &mut HashMap<String, String>,
+    range: &intspan::Range,
+    subs: &[(String, intspan::Range)],
+) -> String {
+    let mut seq = String::new();
+
+    let mut pos = *range.start();
+    for (ctg_id, sub) in subs {
+        if sub.start > pos {
+            seq += &"N".repeat((sub.start - pos) as usize);
+        }
+
+        if !seq_of.contains_key(ctg_id) {
+            let ctg_seq = conn.get_seq(ctg_id);
+            seq_of.insert(ctg_id.to_string(), ctg_seq);
+        }
+        let ctg_seq = seq_of.get(ctg_id).unwrap();
+        let (_, chr_start, _) = conn.get_ctg_pos(ctg_id);
+
+        let ctg_start = (sub.start - chr_start + 1) as usize;
+        let ctg_end = (sub.end - chr_start + 1) as usize;
+
+        // from <= x < to, zero-based
+        seq += ctg_seq.get((ctg_start - 1)..(ctg_end)).unwrap();
+        pos = sub.end + 1;
+    }
+
+    if *range.end() >= pos {
+        seq += &"N".repeat((*range.end() - pos + 1) as usize);
+    }
+
+    seq
+}
+
+/// Counts or aggregated values of rgs in these parts of a range
+fn count_field(
+    lapper_rg_of: &BTreeMap<String, Lapper<u32, String>>,
+    parts: &[(&String, &intspan::Range)],
+    agg: &str,
+) -> String {
+    if agg == "count" {
+        let cnt: i32 = parts
+            .iter()
+            .map(|(ctg_id, range)| gams::count_rg(lapper_rg_of, ctg_id, range))
+            .sum();
+        cnt.to_string()
+    } else {
+        let values: Vec<(f32, i32)> = parts
+            .iter()
+            .flat_map(|(ctg_id, range)| gams::find_rg_value(lapper_rg_of, ctg_id, range))
+            .collect();
+        fmt_value(gams::agg_value(&values, agg))
+    }
+}
+
+/// Empty strings for missing values
+fn fmt_value(value: Option<f32>) -> String {
+    value
+        .map(|v| gams::round(v, 4).to_string())
+        .unwrap_or_default()
+}
+
+/// Each line ends with a newline
+fn wrap_seq(seq: &str, line: usize) -> String {
+    if line == 0 {
+        return format!("{}\n", seq);
+    }
+
+    seq.as_bytes()
+        .chunks(line)
+        .map(|chunk| format!("{}\n", std::str::from_utf8(chunk).unwrap()))
+        .collect()
+}
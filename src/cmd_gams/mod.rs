@@ -1,14 +1,18 @@
 //! Subcommand modules for the `gams` binary.
 
+pub mod alias;
 pub mod anno;
 pub mod clear;
+pub mod edit;
 pub mod env;
 pub mod feature;
 pub mod gen;
 pub mod locate;
 pub mod peak;
+pub mod query;
 pub mod rg;
 pub mod status;
 pub mod sw;
+pub mod swstat;
 pub mod tsv;
 pub mod wave;
@@ -1,5 +1,5 @@
 use clap::*;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
 
 // Create clap subcommand arguments
 pub fn make_subcommand() -> Command {
@@ -9,6 +9,8 @@ pub fn make_subcommand() -> Command {
             r###"
 * There should be only one peak file per chr/ctg
 * Left-/right- wave lengths may be negative
+* --format auto detects .bed and .gff/.gff3/.gtf files by extensions, optionally gzipped
+    * Signals are in the name field of BED and the score field of GFF
 
 "###,
         )
@@ -18,22 +20,28 @@ pub fn make_subcommand() -> Command {
                 .num_args(1)
                 .help("Sets the input file to use"),
         )
+        .arg(gams::format_arg())
 }
 
 // command implementation
 pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     // opts
     let infile = args.get_one::<String>("infile").unwrap();
+    let opt_format = gams::detect_format(infile, args.get_one::<String>("format").unwrap());
+    if opt_format == "vcf" {
+        anyhow::bail!("VCF files can't be used as peaks");
+    }
 
     // redis connection
     let mut conn = gams::Conn::new();
 
     // index of ctgs
     let lapper_of = conn.get_idx_ctg();
+    let alias_of = conn.get_chr_alias();
 
     // ctg_id => [(Range, signal)]
     eprintln!("Loading peaks...");
-    let peaks_of = gams::read_peak(infile, &lapper_of);
+    let peaks_of = gams::read_peak(infile, &opt_format, &lapper_of, &alias_of);
 
     // start serial of each ctg
     // To minimize expensive Redis operations, locally increment the serial number
@@ -58,8 +66,8 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         let parent = intspan::IntSpan::from_pair(chr_start, chr_end);
         let seq: String = conn.get_seq(ctg_id);
 
-        // local caches of GC-content for each ctg
-        let mut cache: HashMap<String, f32> = HashMap::new();
+        // GC-contents of any region of this ctg
+        let counts = gams::BaseCount::new(&seq);
 
         // each peak
         let mut peaks: Vec<gams::Peak> = Default::default();
@@ -77,7 +85,10 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
             *serial += 1;
             let peak_id = format!("peak:{ctg_id}:{serial}");
 
-            let gc_content = gams::cache_gc_content(&tp.0, &parent, &seq, &mut cache);
+            // peaks across ctgs are clipped
+            let clipped = parent.intersect(&intspan::IntSpan::from_pair(tp.0.start, tp.0.end));
+            let gc_rg = intspan::Range::from(&chr_id, clipped.min(), clipped.max());
+            let gc_content = gams::range_gc_content(&gc_rg, &parent, &counts);
 
             let peak = gams::Peak {
                 id: peak_id.clone(),
@@ -165,7 +176,7 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         let peaks = s_peaks_of.get(ctg_id).unwrap();
         for peak in peaks {
             let json = serde_json::to_string(peak).unwrap();
-            conn.pipe_add(&*peak.id, &json);
+            conn.pipe_add(&peak.id, &json);
         }
     }
     conn.pipe_submit(); // Possible remaining records in the pipe
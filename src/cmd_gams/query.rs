@@ -0,0 +1,212 @@
+use clap::*;
+use std::collections::{BTreeSet, HashMap};
+
+// Create clap subcommand arguments
+pub fn make_subcommand() -> Command {
+    Command::new("query")
+        .about("Query stored features, rgs or peaks overlapping the given ranges")
+        .after_help(
+            r###"
+* Ctgs overlapping each range are located with the index of ctgs
+* Each record is reported once, in the order of the given ranges
+* --tag applies to features and rgs
+* With `--file`, --format auto detects .bed, .gff/.gff3/.gtf and .vcf files by extensions
+* --json writes one JSON object per line, as stored in Redis
+
+"###,
+        )
+        .arg(
+            Arg::new("ranges")
+                .required(true)
+                .index(1)
+                .num_args(1..)
+                .help("The given ranges, separating by spaces"),
+        )
+        .arg(
+            Arg::new("group")
+                .long("group")
+                .short('g')
+                .num_args(1)
+                .value_parser([
+                    builder::PossibleValue::new("feature"),
+                    builder::PossibleValue::new("rg"),
+                    builder::PossibleValue::new("peak"),
+                ])
+                .default_value("feature")
+                .help("Which group of records"),
+        )
+        .arg(
+            Arg::new("tag")
+                .long("tag")
+                .short('t')
+                .num_args(1)
+                .action(ArgAction::Append)
+                .help("Only records with these tags"),
+        )
+        .arg(
+            Arg::new("file")
+                .long("file")
+                .short('f')
+                .action(ArgAction::SetTrue)
+                .help("Treat ranges as filenames"),
+        )
+        .arg(gams::format_arg())
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .help("Write JSON lines instead of TSV"),
+        )
+        .arg(
+            Arg::new("outfile")
+                .long("outfile")
+                .short('o')
+                .num_args(1)
+                .default_value("stdout")
+                .help("Output filename. [stdout] for screen"),
+        )
+}
+
+// command implementation
+pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
+    //----------------------------
+    // Options
+    //----------------------------
+    let opt_group = args.get_one::<String>("group").unwrap().as_str();
+    let opt_format = args.get_one::<String>("format").unwrap().as_str();
+    let is_file = args.get_flag("file");
+    let is_json = args.get_flag("json");
+    let tags: Option<BTreeSet<String>> = args
+        .get_many::<String>("tag")
+        .map(|v| v.map(|e| e.to_string()).collect());
+
+    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap());
+
+    // all ranges
+    let mut rgs: Vec<intspan::Range> = vec![];
+    for input in args.get_many::<String>("ranges").unwrap() {
+        if is_file {
+            rgs.extend(gams::read_ranges(input, opt_format));
+        } else {
+            let range = intspan::Range::from_str(input);
+            if range.is_valid() {
+                rgs.push(range);
+            }
+        }
+    }
+
+    // redis connection
+    let mut conn = gams::Conn::new();
+    let lapper_ctg_of = conn.get_idx_ctg();
+    let alias_of = conn.get_chr_alias();
+
+    // local caches of records in each ctg, (id, tag, Range, json) in the order of serials
+    let mut records_of: HashMap<String, Vec<Record>> = HashMap::new();
+
+    // ids of reported records
+    let mut seen: BTreeSet<String> = BTreeSet::new();
+    let mut jsons: Vec<String> = vec![];
+
+    let mut unresolved: BTreeSet<String> = BTreeSet::new();
+    for mut range in rgs {
+        *range.strand_mut() = "".to_string();
+        gams::resolve_chr(&alias_of, &mut range);
+        if !lapper_ctg_of.contains_key(range.chr()) {
+            unresolved.insert(range.chr().to_string());
+            continue;
+        }
+        let ints = intspan::IntSpan::from_pair(range.start, range.end);
+
+        for (ctg_id, _) in gams::find_all_idx(&lapper_ctg_of, &range) {
+            if !records_of.contains_key(&ctg_id) {
+                let values = conn.get_scan_values(&format!("{}:{}:*", opt_group, ctg_id));
+                records_of.insert(ctg_id.clone(), to_records(opt_group, values));
+            }
+
+            for (id, tag, rg, json) in records_of.get(&ctg_id).unwrap() {
+                if seen.contains(id) {
+                    continue;
+                }
+
+                if let Some(tags) = &tags {
+                    if opt_group != "peak" && !tags.contains(tag) {
+                        continue;
+                    }
+                }
+
+                if rg.chr() != range.chr()
+                    || ints
+                        .intersect(&intspan::IntSpan::from_pair(rg.start, rg.end))
+                        .is_empty()
+                {
+                    continue;
+                }
+
+                seen.insert(id.to_string());
+                jsons.push(json.to_string());
+            }
+        }
+    }
+    gams::report_unresolved(&unresolved);
+
+    //----------------------------
+    // Output
+    //----------------------------
+    if is_json {
+        for json in &jsons {
+            writer.write_fmt(format_args!("{}\n", json))?;
+        }
+    } else {
+        let mut tsv_wtr = csv::WriterBuilder::new()
+            .delimiter(b'\t')
+            .has_headers(true)
+            .from_writer(writer);
+
+        for json in &jsons {
+            gams::write_record(&mut tsv_wtr, opt_group, json)?;
+        }
+        tsv_wtr.flush()?;
+    }
+
+    eprintln!(
+        "There are {} {}s overlapping the given ranges",
+        jsons.len(),
+        opt_group
+    );
+
+    Ok(())
+}
+
+/// (id, tag, Range, json)
+type Record = (String, String, intspan::Range, String);
+
+/// Records are sorted by serials
+///
+/// Tags come from the structs, so rgs stored without a tag get the serde default
+fn to_records(group: &str, jsons: Vec<String>) -> Vec<Record> {
+    let mut records: Vec<(i32, Record)> = jsons
+        .into_iter()
+        .map(|json| {
+            let (id, tag, range) = match group {
+                "feature" => {
+                    let f: gams::Feature = serde_json::from_str(&json).unwrap();
+                    (f.id, f.tag, f.range)
+                }
+                "rg" => {
+                    let r: gams::Rg = serde_json::from_str(&json).unwrap();
+                    (r.id, r.tag, r.range)
+                }
+                // peaks have no tags
+                _ => {
+                    let p: gams::Peak = serde_json::from_str(&json).unwrap();
+                    (p.id, "".to_string(), p.range)
+                }
+            };
+            let rg = intspan::Range::from_str(&range);
+            (gams::extract_serial(&id), (id, tag, rg, json))
+        })
+        .collect();
+    records.sort_by_key(|(serial, _)| *serial);
+
+    records.into_iter().map(|(_, record)| record).collect()
+}
@@ -7,6 +7,13 @@ pub fn make_subcommand() -> Command {
         .about("Add range files for counting")
         .after_help(
             r###"
+* --format auto detects .bed, .gff/.gff3/.gtf and .vcf files by extensions, optionally gzipped
+    * Others are treated as range files, e.g. `I:100-200`
+* --value N takes the Nth field, 1-based, as the value of each rg
+    * e.g., 5 for the score of BED, 6 for QUAL of VCF
+    * Values are aggregated by `locate --count --agg` and `sw --action count --agg`
+* Each --tag has its own index, so `locate --count` and `sw` can count tags separately
+
 "###,
         )
         .arg(
@@ -23,27 +30,52 @@ pub fn make_subcommand() -> Command {
                 .value_parser(value_parser!(usize))
                 .help("Batch size for one Redis submission"),
         )
+        .arg(gams::format_arg())
+        .arg(
+            Arg::new("tag")
+                .long("tag")
+                .short('t')
+                .num_args(1)
+                .default_value("rg")
+                .help("Tag of rgs in these files"),
+        )
+        .arg(
+            Arg::new("value")
+                .long("value")
+                .num_args(1)
+                .default_value("0")
+                .value_parser(value_parser!(usize))
+                .help("Field of values, 1-based. 0 for no values"),
+        )
 }
 
 // command implementation
 pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     // opts
     let opt_size = *args.get_one::<usize>("size").unwrap();
+    let opt_format = args.get_one::<String>("format").unwrap().as_str();
+    let opt_value = *args.get_one::<usize>("value").unwrap();
+    let opt_tag = args.get_one::<String>("tag").unwrap().as_str();
 
     // redis connection
     let mut conn = gams::Conn::with_size(opt_size);
 
     // index of ctgs
     let lapper_of = conn.get_idx_ctg();
+    let alias_of = conn.get_chr_alias();
 
     // processing each file
     for infile in args.get_many::<String>("infiles").unwrap() {
-        // ctg_id => [Range]
+        // ctg_id => [(Range, value)]
         // act as a sorter
-        let ranges_of = gams::read_range(infile, &lapper_of);
+        let ranges_of =
+            gams::read_range_value(infile, opt_format, opt_value, &lapper_of, &alias_of);
 
-        // (ctg_id, Range)
-        let ctg_ranges = gams::ctg_range_tuple(&ranges_of);
+        // (ctg_id, Range, value)
+        let ctg_ranges: Vec<(&String, &intspan::Range, &Option<f32>)> = ranges_of
+            .iter()
+            .flat_map(|(k, v)| v.iter().map(move |(r, value)| (k, r, value)))
+            .collect();
 
         // total number of ranges
         eprintln!("There are {} rgs in this file", ctg_ranges.len());
@@ -53,15 +85,15 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         // For each ctg, we increase the counter in Redis only once
         let mut serial_of: BTreeMap<String, i32> = BTreeMap::new();
 
-        for (i, (ctg_id, range)) in ctg_ranges.iter().enumerate() {
+        for (i, (ctg_id, range, value)) in ctg_ranges.iter().enumerate() {
             // prompts
             if i > 1 && i % (opt_size * 10) == 0 {
                 eprintln!("Insert {} records", i);
             }
 
             // serial and id
-            if !serial_of.contains_key(ctg_id) {
-                let cnt = ranges_of.get(ctg_id).unwrap().len() as i32;
+            if !serial_of.contains_key(*ctg_id) {
+                let cnt = ranges_of.get(*ctg_id).unwrap().len() as i32;
                 // Redis counter
                 // increase serial by cnt
                 let serial = conn.incr_sn_n(&format!("cnt:rg:{ctg_id}"), cnt);
@@ -70,13 +102,15 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
                 serial_of.insert(ctg_id.to_string(), serial - cnt);
             }
 
-            let serial = serial_of.get_mut(ctg_id).unwrap();
+            let serial = serial_of.get_mut(*ctg_id).unwrap();
             *serial += 1;
             let rg_id = format!("rg:{ctg_id}:{serial}");
 
             let rg = gams::Rg {
                 id: rg_id.clone(),
                 range: range.to_string(),
+                tag: opt_tag.to_string(),
+                value: **value,
             };
             let json = serde_json::to_string(&rg).unwrap();
             conn.pipe_add(&rg_id, &json);
@@ -1,11 +1,53 @@
 use clap::*;
 use itertools::Itertools;
-use std::collections::{HashMap, HashSet};
+use rust_lapper::Lapper;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 // Create clap subcommand arguments
 pub fn make_subcommand() -> Command {
     Command::new("sw")
         .about("Sliding windows around features/peaks")
+        .after_help(
+            r###"
+* Windows follow the strand of features
+    * `M` is the anchor window, `U`/`D` are upstream/downstream windows
+    * Unstranded features have `L`/`R` windows in chromosome orientation
+* `gams sw peak` builds windows around peaks inserted by `gams peak`
+    * Peaks are unstranded, and --tag, --type and --biotype are ignored
+    * peak_signal, peak_gc, left_wave_length and right_wave_length of the peak are appended
+* Multiple --size share sequences and caches of each ctg
+    * The base_size column tells them apart, and ids are numbered across sizes
+    * base_size is the --size of a window, not its width
+    * With --log, flanking windows are size, 2 * size, 4 * size and so on
+* --style applies to --anchor center
+    * intact - the feature itself is `M` at full length, flanking windows start at its edges
+    * center - `M` is a --size window at the center of the feature
+* --anchor
+    * center - `M` at the center of the feature
+    * 5p/3p - `M` at the 5' or 3' end of the feature, e.g. the TSS of a gene
+    * body - the feature is scaled into --bins `B` windows, numbered from the 5' end
+* --action gibbs computes the free energy of the DNA duplex of each window, kcal/mol
+    * gibbs_mean and gibbs_stddev are calculated over the --resize window, like gc_mean
+    * --temp and --salt set the temperature and the Na+ concentration
+    * Windows with Ns have empty values
+* --action count needs rgs inserted by `gams rg`
+    * rg_count is the number of overlapping rgs, rg_density is rg_count per kb
+    * rg_value aggregates values of rgs by --agg, sum, mean, max or wmean
+    * Each --rg-tag appends `{tag}_count` and `{tag}_value` columns
+* Each --anno appends a `{name}Prop` column, like `gams anno`
+    * `--anno cds=cds.json --anno repeats=repeats.json` gives cdsProp and repeatsProp
+    * The name defaults to the file stem of the runlist
+* --db stores windows as JSON, which can be exported by `gams tsv --scan 'sw:*'`
+    * Ids are `sw:{target_id}:{n}`, so reruns overwrite windows of the same targets
+    * Run `gams clear sw` before changing --size, --max or --anchor
+    * `gams tsv` exports the union of columns of all windows, including per-tag and --anno ones
+
+* Running in parallel mode will active 1 reader, 1 writer (the main thread)
+  and the corresponding number of workers
+    * The writer reorders results, so the output is identical to serial mode
+
+"###,
+        )
         .arg(
             Arg::new("target")
                 .required(false)
@@ -44,12 +86,57 @@ pub fn make_subcommand() -> Command {
                 .default_value("intact")
                 .help("Style of sliding windows, intact or center"),
         )
+        .arg(
+            Arg::new("tag")
+                .long("tag")
+                .num_args(1)
+                .action(ArgAction::Append)
+                .help("Only features with these tags"),
+        )
+        .arg(
+            Arg::new("type")
+                .long("type")
+                .num_args(1)
+                .action(ArgAction::Append)
+                .help("Only features of these types, e.g. gene"),
+        )
+        .arg(
+            Arg::new("biotype")
+                .long("biotype")
+                .num_args(1)
+                .action(ArgAction::Append)
+                .help("Only features of these biotypes, e.g. protein_coding"),
+        )
+        .arg(
+            Arg::new("anchor")
+                .long("anchor")
+                .num_args(1)
+                .value_parser([
+                    builder::PossibleValue::new("center"),
+                    builder::PossibleValue::new("5p"),
+                    builder::PossibleValue::new("3p"),
+                    builder::PossibleValue::new("body"),
+                ])
+                .default_value("center")
+                .help("Where windows are anchored on features"),
+        )
+        .arg(
+            Arg::new("bins")
+                .long("bins")
+                .num_args(1)
+                .value_parser(value_parser!(i32))
+                .default_value("10")
+                .help("Number of body windows of --anchor body"),
+        )
         .arg(
             Arg::new("size")
                 .long("size")
                 .num_args(1)
+                .action(ArgAction::Append)
+                .value_delimiter(',')
                 .value_parser(value_parser!(i32))
-                .default_value("100"),
+                .default_value("100")
+                .help("Sizes of windows, e.g. `--size 50,100,1000`"),
         )
         .arg(
             Arg::new("max")
@@ -58,13 +145,71 @@ pub fn make_subcommand() -> Command {
                 .value_parser(value_parser!(i32))
                 .default_value("20"),
         )
+        .arg(
+            Arg::new("log")
+                .long("log")
+                .action(ArgAction::SetTrue)
+                .help("Log-spaced distance bins, widths of flanking windows are doubled each step"),
+        )
         .arg(
             Arg::new("resize")
                 .long("resize")
                 .num_args(1)
                 .value_parser(value_parser!(i32))
                 .default_value("500")
-                .help("GC-stat flanking region size"),
+                .help("GC-stat and gibbs-stat flanking region size"),
+        )
+        .arg(
+            Arg::new("agg")
+                .long("agg")
+                .num_args(1)
+                .value_parser([
+                    builder::PossibleValue::new("count"),
+                    builder::PossibleValue::new("sum"),
+                    builder::PossibleValue::new("mean"),
+                    builder::PossibleValue::new("max"),
+                    builder::PossibleValue::new("wmean"),
+                ])
+                .default_value("count")
+                .help("Aggregation of rg values in --action count"),
+        )
+        .arg(
+            Arg::new("rg-tag")
+                .long("rg-tag")
+                .num_args(1)
+                .action(ArgAction::Append)
+                .help("Count rgs of these tags separately, see `gams rg --tag`"),
+        )
+        .arg(
+            Arg::new("temp")
+                .long("temp")
+                .num_args(1)
+                .value_parser(value_parser!(f32))
+                .default_value("37.0")
+                .help("Temperature of --action gibbs, in degree centigrade"),
+        )
+        .arg(
+            Arg::new("salt")
+                .long("salt")
+                .num_args(1)
+                .value_parser(value_parser!(f32))
+                .default_value("1.0")
+                .help("Salt concentration of --action gibbs, Na+ in M"),
+        )
+        .arg(
+            Arg::new("anno")
+                .long("anno")
+                .num_args(1)
+                .action(ArgAction::Append)
+                .help(
+                    "Append the proportion of each window covered by runlists, `name=runlist.json`",
+                ),
+        )
+        .arg(
+            Arg::new("db")
+                .long("db")
+                .action(ArgAction::SetTrue)
+                .help("Also insert windows into Redis as `sw:{target_id}:{n}`"),
         )
         .arg(
             Arg::new("parallel")
@@ -98,20 +243,45 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
         ctgs.push(ctg_of.get(ctg_id).unwrap().clone())
     }
 
+    // (name, runlist set) of each --anno
+    let mut annos: Vec<Anno> = vec![];
+    if let Some(values) = args.get_many::<String>("anno") {
+        let alias_of = conn.get_chr_alias();
+        let chrs: BTreeSet<String> = conn.get_vec_chr().into_iter().collect();
+        for value in values {
+            let (name, runlist) = match value.split_once('=') {
+                Some((name, runlist)) => (name.to_string(), runlist),
+                None => (anno_name(value), value.as_str()),
+            };
+            let json = intspan::read_json(runlist);
+            let set = gams::resolve_set(&intspan::json2set(&json), &alias_of, &chrs);
+            annos.push((name, set));
+        }
+    }
+
     eprintln!("{} contigs to be processed", ctgs.len());
 
-    proc_ctg_p(&ctgs, args)?;
+    proc_ctg_p(&ctgs, &annos, args)?;
 
     Ok(())
 }
 
-fn proc_ctg(ctg: &gams::Ctg, args: &ArgMatches) -> String {
+fn proc_ctg(ctg: &gams::Ctg, annos: &[Anno], args: &ArgMatches) -> String {
     //----------------------------
     // Args
     //----------------------------
-    let opt_size = *args.get_one::<i32>("size").unwrap();
+    let opt_sizes: Vec<i32> = args.get_many::<i32>("size").unwrap().copied().collect();
+    let is_log = args.get_flag("log");
     let opt_max = *args.get_one::<i32>("max").unwrap();
     let opt_resize = *args.get_one::<i32>("resize").unwrap();
+    let opt_target = args.get_one::<String>("target").unwrap().as_str();
+    let opt_style = args.get_one::<String>("style").unwrap().as_str();
+    let opt_anchor = args.get_one::<String>("anchor").unwrap().as_str();
+    let opt_bins = *args.get_one::<i32>("bins").unwrap();
+    let opt_agg = args.get_one::<String>("agg").unwrap().as_str();
+    let opt_temp = *args.get_one::<f32>("temp").unwrap();
+    let opt_salt = *args.get_one::<f32>("salt").unwrap();
+    let is_db = args.get_flag("db");
 
     let mut actions: HashSet<String> = HashSet::new();
     for action in args.get_many::<String>("action").unwrap() {
@@ -123,59 +293,160 @@ fn proc_ctg(ctg: &gams::Ctg, args: &ArgMatches) -> String {
 
     eprintln!("Process {} {}", ctg.id, ctg.range);
 
-    // local caches of GC-content for each ctg
-    let mut cache: HashMap<String, f32> = HashMap::new();
+    // local caches of free energies for each ctg
+    let dg = gams::DeltaG::from(opt_temp, opt_salt);
+    let mut cache_dg: HashMap<String, Option<f32>> = HashMap::new();
 
     let parent = intspan::IntSpan::from_pair(ctg.chr_start, ctg.chr_end);
     let seq: String = conn.get_seq(&ctg.id);
 
-    // All features in this ctg
-    let jsons: Vec<String> = conn.get_scan_values(&format!("feature:{}:*", ctg.id));
-    let features: Vec<gams::Feature> = jsons
-        .iter()
-        .map(|el| serde_json::from_str(el).unwrap())
-        .collect();
-    eprintln!("\tThere are {} features", features.len());
+    // GC-contents of any region of this ctg
+    let counts = gams::BaseCount::new(&seq);
+
+    // local caches of each runlist set in this ctg
+    let mut cache_annos: Vec<HashMap<String, intspan::IntSpan>> = vec![HashMap::new(); annos.len()];
+
+    // names of per-tag and --anno columns
+    let tag_names = rg_tags(args);
+    let anno_names: Vec<String> = annos.iter().map(|(name, _)| name.to_string()).collect();
+
+    // index of rgs in this ctg, all rgs and rgs of each tag
+    let mut lapper_rg_of: BTreeMap<String, Lapper<u32, String>> = BTreeMap::new();
+    let mut lapper_tag_ofs: Vec<BTreeMap<String, Lapper<u32, String>>> = vec![];
+    if actions.contains("count") {
+        lapper_rg_of.insert(ctg.id.clone(), conn.get_idx_rg_ctg(&ctg.id, None));
+        for tag in &tag_names {
+            let lapper = conn.get_idx_rg_ctg(&ctg.id, Some(tag));
+            lapper_tag_ofs.push(BTreeMap::from([(ctg.id.clone(), lapper)]));
+        }
+    }
+
+    // (id, tag, Range, strand, Peak) of features or peaks in this ctg
+    let targets: Vec<Target> = if opt_target == "peak" {
+        let jsons: Vec<String> = conn.get_scan_values(&format!("peak:{}:*", ctg.id));
+        let peaks: Vec<gams::Peak> = jsons
+            .iter()
+            .map(|el| serde_json::from_str(el).unwrap())
+            .collect();
+        eprintln!("\tThere are {} peaks", peaks.len());
+
+        // peaks are unstranded
+        peaks
+            .into_iter()
+            .map(|p| {
+                let rg = intspan::Range::from_str(&p.range);
+                (
+                    p.id.clone(),
+                    "peak".to_string(),
+                    rg,
+                    "".to_string(),
+                    Some(p),
+                )
+            })
+            .collect()
+    } else {
+        let jsons: Vec<String> = conn.get_scan_values(&format!("feature:{}:*", ctg.id));
+        let features: Vec<gams::Feature> = jsons
+            .iter()
+            .map(|el| serde_json::from_str(el).unwrap())
+            .filter(|f: &gams::Feature| {
+                is_selected(args, "tag", Some(&f.tag))
+                    && is_selected(args, "type", f.feature_type.as_ref())
+                    && is_selected(args, "biotype", f.biotype.as_ref())
+            })
+            .collect();
+        eprintln!("\tThere are {} features", features.len());
+
+        features
+            .into_iter()
+            .map(|f| {
+                let rg = intspan::Range::from_str(&f.range);
+                let strand = f.strand.clone().unwrap_or(rg.strand().to_string());
+                (f.id, f.tag, rg, strand, None)
+            })
+            .collect()
+    };
 
     let mut out_string = "".to_string();
-    for feature in &features {
-        let feature_id = &feature.id;
-        let feature_range = intspan::Range::from_str(&feature.range);
-        let range_start = feature_range.start;
-        let range_end = feature_range.end;
+    for (target_id, target_tag, target_range, strand, peak) in &targets {
+        let range_start = target_range.start;
+        let range_end = target_range.end;
 
-        // No need to use Redis counters
-        let mut sn: isize = 1;
+        // 5' and 3' ends in the direction of transcription
+        let (five, three) = if strand == "-" {
+            (range_end, range_start)
+        } else {
+            (range_start, range_end)
+        };
 
-        let windows = gams::center_sw(&parent, range_start, range_end, opt_size, opt_max);
+        // windows of all sizes, (IntSpan, type, distance, base_size)
+        let mut windows: Vec<(intspan::IntSpan, String, i32, i32)> = vec![];
+        for &size in &opt_sizes {
+            let sws = match opt_anchor {
+                "5p" => gams::center_sw(&parent, five, five, size, opt_max, is_log),
+                "3p" => gams::center_sw(&parent, three, three, size, opt_max, is_log),
+                "body" => gams::body_sw(
+                    &parent,
+                    range_start,
+                    range_end,
+                    size,
+                    opt_max,
+                    is_log,
+                    opt_bins,
+                ),
+                _ if opt_style == "intact" => {
+                    gams::intact_sw(&parent, range_start, range_end, size, opt_max, is_log)
+                }
+                _ => gams::center_sw(&parent, range_start, range_end, size, opt_max, is_log),
+            };
 
-        for (sw_ints, sw_type, sw_distance) in windows {
-            let sw_id = format!("sw:{}:{}", feature_id, sn);
+            let sws = gams::orient_sw(sws, strand, opt_bins);
+            windows.extend(sws.into_iter().map(|(ints, t, d)| (ints, t, d, size)));
+        }
+
+        // No need to use Redis counters
+        for (i, (sw_ints, sw_type, sw_distance, sw_size)) in windows.into_iter().enumerate() {
+            let sw_id = format!("sw:{}:{}", target_id, i + 1);
 
             let mut sw = gams::Sw {
                 id: sw_id,
                 range: intspan::Range::from(&ctg.chr_id, sw_ints.min(), sw_ints.max()).to_string(),
+                tag: target_tag.to_string(),
                 sw_type,
                 distance: sw_distance,
+                base_size: sw_size,
                 gc_content: None,
                 gc_mean: None,
                 gc_stddev: None,
                 gc_cv: None,
+                gibbs: None,
+                gibbs_mean: None,
+                gibbs_stddev: None,
                 rg_count: None,
+                rg_density: None,
+                rg_value: None,
+                tag_names: tag_names.clone(),
+                tag_counts: vec![],
+                tag_values: vec![],
+                peak_signal: peak.as_ref().map(|p| p.signal.clone()),
+                peak_gc: peak.as_ref().map(|p| p.gc),
+                left_wave_length: peak.as_ref().and_then(|p| p.left_wave_length),
+                right_wave_length: peak.as_ref().and_then(|p| p.right_wave_length),
+                anno_names: anno_names.clone(),
+                anno_props: vec![],
             };
 
             if actions.contains("gc") {
-                let gc_content = gams::cache_gc_content(
+                let gc_content = gams::range_gc_content(
                     &intspan::Range::from(&ctg.chr_id, sw_ints.min(), sw_ints.max()),
                     &parent,
-                    &seq,
-                    &mut cache,
+                    &counts,
                 );
 
                 let resized = gams::center_resize(&parent, &sw_ints, opt_resize);
                 let re_rg = intspan::Range::from(&ctg.chr_id, resized.min(), resized.max());
                 let (gc_mean, gc_stddev, gc_cv) =
-                    gams::cache_gc_stat(&re_rg, &parent, &seq, &mut cache, opt_size, opt_size);
+                    gams::range_gc_stat(&re_rg, &parent, &counts, sw_size, sw_size);
 
                 sw.gc_content = Some(gc_content);
                 sw.gc_mean = Some(gc_mean);
@@ -183,50 +454,154 @@ fn proc_ctg(ctg: &gams::Ctg, args: &ArgMatches) -> String {
                 sw.gc_cv = Some(gc_cv);
             }
 
-            sn += 1;
+            if actions.contains("gibbs") {
+                let sw_rg = intspan::Range::from(&ctg.chr_id, sw_ints.min(), sw_ints.max());
+                sw.gibbs = gams::cache_gibbs(&sw_rg, &parent, &seq, &dg, &mut cache_dg);
+
+                let resized = gams::center_resize(&parent, &sw_ints, opt_resize);
+                let re_rg = intspan::Range::from(&ctg.chr_id, resized.min(), resized.max());
+                let (gibbs_mean, gibbs_stddev) = gams::cache_gibbs_stat(
+                    &re_rg,
+                    &parent,
+                    &seq,
+                    &dg,
+                    &mut cache_dg,
+                    sw_size,
+                    sw_size,
+                );
+                sw.gibbs_mean = gibbs_mean;
+                sw.gibbs_stddev = gibbs_stddev;
+            }
+
+            if actions.contains("count") {
+                let sw_rg = intspan::Range::from(&ctg.chr_id, sw_ints.min(), sw_ints.max());
+                let rg_count = gams::count_rg(&lapper_rg_of, &ctg.id, &sw_rg);
+                sw.rg_count = Some(rg_count);
+                sw.rg_density = Some(gams::round(
+                    rg_count as f32 * 1000.0 / sw_ints.size() as f32,
+                    4,
+                ));
+
+                if opt_agg != "count" {
+                    let values = gams::find_rg_value(&lapper_rg_of, &ctg.id, &sw_rg);
+                    sw.rg_value = gams::agg_value(&values, opt_agg).map(|v| gams::round(v, 4));
+                }
+
+                for lapper_tag_of in &lapper_tag_ofs {
+                    sw.tag_counts
+                        .push(gams::count_rg(lapper_tag_of, &ctg.id, &sw_rg));
+
+                    if opt_agg != "count" {
+                        let values = gams::find_rg_value(lapper_tag_of, &ctg.id, &sw_rg);
+                        sw.tag_values
+                            .push(gams::agg_value(&values, opt_agg).map(|v| gams::round(v, 4)));
+                    }
+                }
+            }
+
+            if !annos.is_empty() {
+                let sw_rg = intspan::Range::from(&ctg.chr_id, sw_ints.min(), sw_ints.max());
+                sw.anno_props = annos
+                    .iter()
+                    .zip(cache_annos.iter_mut())
+                    .map(|((_, set), cache)| {
+                        gams::round(gams::cache_prop(set, &ctg.id, &parent, &sw_rg, cache), 4)
+                    })
+                    .collect();
+            }
+
+            if is_db {
+                let json = serde_json::to_string(&sw).unwrap();
+                conn.pipe_add(&sw.id, &json);
+            }
 
             // outputs
             out_string += &format!("{}\n", sw);
         }
     }
+    conn.pipe_submit(); // Possible remaining records in the pipe
 
     out_string
 }
 
+/// (id, tag, Range, strand, Peak)
+type Target = (String, String, intspan::Range, String, Option<gams::Peak>);
+
+/// (name, runlist set)
+type Anno = (String, BTreeMap<String, intspan::IntSpan>);
+
+/// Without `name=`, the name is the first part of the file stem, as `gams anno` does
+fn anno_name(runlist: &str) -> String {
+    std::path::Path::new(runlist)
+        .file_stem()
+        .and_then(std::ffi::OsStr::to_str)
+        .unwrap()
+        .split('.')
+        .next()
+        .unwrap()
+        .to_string()
+}
+
+/// Tags of --rg-tag, only with --action count
+fn rg_tags(args: &ArgMatches) -> Vec<String> {
+    let is_count = args
+        .get_many::<String>("action")
+        .unwrap()
+        .any(|e| e == "count");
+    match args.get_many::<String>("rg-tag") {
+        Some(tags) if is_count => tags.map(|e| e.to_string()).collect(),
+        _ => vec![],
+    }
+}
+
+/// Features pass when the filter isn't given or the field is one of the given values
+fn is_selected(args: &ArgMatches, filter: &str, field: Option<&String>) -> bool {
+    match args.get_many::<String>(filter) {
+        None => true,
+        Some(mut values) => field.is_some_and(|f| values.any(|v| v == f)),
+    }
+}
+
 // Adopt from https://rust-lang-nursery.github.io/rust-cookbook/concurrency/threads.html#create-a-parallel-pipeline
-fn proc_ctg_p(ctgs: &Vec<gams::Ctg>, args: &ArgMatches) -> anyhow::Result<()> {
+fn proc_ctg_p(ctgs: &[gams::Ctg], annos: &[Anno], args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Args
     //----------------------------
     let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap());
     let opt_parallel = *args.get_one::<usize>("parallel").unwrap();
 
-    // headers
-    let headers = [
-        "id",
-        "range",
-        "type",
-        "distance",
-        "gc_content",
-        "gc_mean",
-        "gc_stddev",
-        "gc_cv",
-        "rg_count",
-    ];
+    // headers, from a window with all optional columns of this run
+    let tag_names = rg_tags(args);
+    let is_value = args.get_one::<String>("agg").unwrap() != "count";
+    let template = gams::Sw {
+        tag_values: if is_value {
+            vec![None; tag_names.len()]
+        } else {
+            vec![]
+        },
+        tag_names,
+        peak_signal: (args.get_one::<String>("target").unwrap() == "peak").then(String::new),
+        anno_names: annos.iter().map(|(name, _)| name.to_string()).collect(),
+        ..Default::default()
+    };
+    let headers = template.headers();
     writer.write_all(format!("{}\n", headers.join("\t")).as_ref())?;
 
-    // Channel 1 - Contigs
-    let (snd1, rcv1) = crossbeam::channel::bounded::<gams::Ctg>(10);
+    // Channel 1 - Contigs, with their indexes
+    let (snd1, rcv1) = crossbeam::channel::bounded::<(usize, gams::Ctg)>(10);
     // Channel 2 - Results
-    let (snd2, rcv2) = crossbeam::channel::bounded::<String>(10);
+    let (snd2, rcv2) = crossbeam::channel::bounded::<(usize, String)>(10);
+    // Channel 3 - Tickets, limits contigs in flight and thus the reorder buffer
+    let (snd3, rcv3) = crossbeam::channel::bounded::<()>(opt_parallel * 4);
 
     crossbeam::scope(|s| {
         //----------------------------
         // Reader thread
         //----------------------------
         s.spawn(|_| {
-            for ctg in ctgs {
-                snd1.send(ctg.clone()).unwrap();
+            for (i, ctg) in ctgs.iter().enumerate() {
+                snd3.send(()).unwrap();
+                snd1.send((i, ctg.clone())).unwrap();
             }
             // Close the channel - this is necessary to exit the for-loop in the worker
             drop(snd1);
@@ -241,9 +616,9 @@ fn proc_ctg_p(ctgs: &Vec<gams::Ctg>, args: &ArgMatches) -> anyhow::Result<()> {
             // Spawn workers in separate threads
             s.spawn(move |_| {
                 // Receive until channel closes
-                for ctg in recvr.iter() {
-                    let out_string = proc_ctg(&ctg, args);
-                    sendr.send(out_string).unwrap();
+                for (i, ctg) in recvr.iter() {
+                    let out_string = proc_ctg(&ctg, annos, args);
+                    sendr.send((i, out_string)).unwrap();
                 }
             });
         }
@@ -253,8 +628,16 @@ fn proc_ctg_p(ctgs: &Vec<gams::Ctg>, args: &ArgMatches) -> anyhow::Result<()> {
         //----------------------------
         // Writer (main) thread
         //----------------------------
-        for out_string in rcv2.iter() {
-            writer.write_all(out_string.as_ref()).unwrap();
+        // results are written in the order of contigs
+        let mut pending: BTreeMap<usize, String> = BTreeMap::new();
+        let mut next = 0;
+        for (i, out_string) in rcv2.iter() {
+            pending.insert(i, out_string);
+            while let Some(out_string) = pending.remove(&next) {
+                writer.write_all(out_string.as_ref()).unwrap();
+                rcv3.recv().unwrap();
+                next += 1;
+            }
         }
     })
     .unwrap();
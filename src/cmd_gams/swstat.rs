@@ -0,0 +1,271 @@
+use clap::*;
+use rand::SeedableRng;
+use std::collections::{BTreeMap, HashMap};
+use std::io::BufRead;
+
+// Create clap subcommand arguments
+pub fn make_subcommand() -> Command {
+    Command::new("swstat")
+        .about("Statistics of sliding windows by tag, type, distance and base_size")
+        .after_help(
+            r###"
+* Reads the output of `gams sw`, or windows stored by `gams sw --db` with --db
+    * Stored windows keep their per-tag and --anno columns
+    * Windows are grouped by tag, type, distance and base_size
+    * Without a tag column, all windows belong to the empty tag
+* Metrics are the numeric columns, e.g. gc_content, gibbs, rg_density and cdsProp
+    * id, range, tag, type, distance, base_size and peak_signal aren't metrics
+    * --metric selects some of them
+    * Empty values are ignored
+* Each line is `tag  type  distance  base_size  metric  count  mean  stddev  median  ci_lower  ci_upper`
+    * ci_lower and ci_upper are the percentile bootstrap confidence interval of the mean
+    * --boot 0 skips the bootstrap, --seed makes resamples reproducible
+    * stddev is empty with fewer than 2 values
+
+"###,
+        )
+        .arg(
+            Arg::new("infiles")
+                .index(1)
+                .num_args(1..)
+                .default_value("stdin")
+                .help("Set the input files to use"),
+        )
+        .arg(
+            Arg::new("db")
+                .long("db")
+                .action(ArgAction::SetTrue)
+                .help("Read windows stored as `sw:*` instead of files"),
+        )
+        .arg(
+            Arg::new("metric")
+                .long("metric")
+                .short('m')
+                .num_args(1)
+                .action(ArgAction::Append)
+                .help("Only these metrics"),
+        )
+        .arg(
+            Arg::new("boot")
+                .long("boot")
+                .num_args(1)
+                .value_parser(value_parser!(usize))
+                .default_value("1000")
+                .help("Number of bootstrap resamples"),
+        )
+        .arg(
+            Arg::new("ci")
+                .long("ci")
+                .num_args(1)
+                .value_parser(value_parser!(f32))
+                .default_value("0.95")
+                .help("Confidence level"),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .num_args(1)
+                .value_parser(value_parser!(u64))
+                .default_value("42")
+                .help("Seed of the random number generator"),
+        )
+        .arg(
+            Arg::new("outfile")
+                .long("outfile")
+                .short('o')
+                .num_args(1)
+                .default_value("stdout")
+                .help("Output filename. [stdout] for screen"),
+        )
+}
+
+// command implementation
+pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
+    //----------------------------
+    // Options
+    //----------------------------
+    let mut writer = intspan::writer(args.get_one::<String>("outfile").unwrap());
+    let opt_boot = *args.get_one::<usize>("boot").unwrap();
+    let opt_ci = *args.get_one::<f32>("ci").unwrap();
+    let opt_seed = *args.get_one::<u64>("seed").unwrap();
+    let metrics: Option<Vec<String>> = args
+        .get_many::<String>("metric")
+        .map(|v| v.map(|e| e.to_string()).collect());
+
+    if !(0.0..1.0).contains(&opt_ci) {
+        anyhow::bail!("--ci should be between 0 and 1");
+    }
+
+    //----------------------------
+    // Loading
+    //----------------------------
+    let mut stat = Stat::default();
+
+    if args.get_flag("db") {
+        // windows are written to TSV, just like `gams tsv --scan 'sw:*'`
+        let mut conn = gams::Conn::new();
+        let sws: Vec<gams::Sw> = conn
+            .get_scan_values("sw:*")
+            .iter()
+            .map(|el| serde_json::from_str(el).unwrap())
+            .collect();
+
+        let mut tsv_wtr = csv::WriterBuilder::new()
+            .delimiter(b'\t')
+            .from_writer(vec![]);
+        gams::write_sws(&mut tsv_wtr, &sws)?;
+        let tsv = String::from_utf8(tsv_wtr.into_inner()?)?;
+
+        stat.add_lines(tsv.lines().map(|e| e.to_string()), &metrics)?;
+    } else {
+        for infile in args.get_many::<String>("infiles").unwrap() {
+            let reader = intspan::reader(infile);
+            stat.add_lines(reader.lines().map_while(Result::ok), &metrics)?;
+        }
+    }
+
+    //----------------------------
+    // Output
+    //----------------------------
+    let headers = [
+        "tag",
+        "type",
+        "distance",
+        "base_size",
+        "metric",
+        "count",
+        "mean",
+        "stddev",
+        "median",
+        "ci_lower",
+        "ci_upper",
+    ];
+    writer.write_fmt(format_args!("{}\n", headers.join("\t")))?;
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(opt_seed);
+    for ((tag, sw_type, distance, size), values_of) in &stat.groups {
+        for metric in &stat.metrics {
+            let values = match values_of.get(metric) {
+                Some(values) if !values.is_empty() => values,
+                _ => continue,
+            };
+
+            let stddev = if values.len() > 1 {
+                fmt_value(gams::stddev(values))
+            } else {
+                "".to_string()
+            };
+            let (ci_lower, ci_upper) = if opt_boot > 0 {
+                let (lower, upper) = gams::bootstrap_ci(values, opt_boot, opt_ci, &mut rng);
+                (fmt_value(lower), fmt_value(upper))
+            } else {
+                ("".to_string(), "".to_string())
+            };
+
+            writer.write_fmt(format_args!(
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                tag,
+                sw_type,
+                distance,
+                size.map(|e| e.to_string()).unwrap_or_default(),
+                metric,
+                values.len(),
+                fmt_value(gams::mean(values)),
+                stddev,
+                fmt_value(gams::median(values)),
+                ci_lower,
+                ci_upper,
+            ))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Columns that aren't metrics
+const NOT_METRICS: [&str; 7] = [
+    "id",
+    "range",
+    "tag",
+    "type",
+    "distance",
+    "base_size",
+    "peak_signal",
+];
+
+/// (tag, type, distance, base_size)
+type Group = (String, String, i32, Option<i32>);
+
+#[derive(Default)]
+struct Stat {
+    /// Metrics in the order of appearance
+    metrics: Vec<String>,
+    /// Values of each metric in each group
+    groups: BTreeMap<Group, HashMap<String, Vec<f32>>>,
+}
+
+impl Stat {
+    /// Lines of a TSV file, the first one is the header
+    fn add_lines(
+        &mut self,
+        mut lines: impl Iterator<Item = String>,
+        metrics: &Option<Vec<String>>,
+    ) -> anyhow::Result<()> {
+        let header = match lines.next() {
+            Some(header) => header,
+            None => return Ok(()),
+        };
+        let fields: Vec<&str> = header.split('\t').collect();
+
+        let idx_of = |name: &str| fields.iter().position(|e| *e == name);
+        let idx_tag = idx_of("tag");
+        let idx_size = idx_of("base_size");
+        let (idx_type, idx_distance) = match (idx_of("type"), idx_of("distance")) {
+            (Some(idx_type), Some(idx_distance)) => (idx_type, idx_distance),
+            _ => anyhow::bail!("Columns of type and distance are needed"),
+        };
+
+        // (index, name) of metric columns
+        let columns: Vec<(usize, String)> = fields
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| !NOT_METRICS.contains(e))
+            .filter(|(_, e)| metrics.as_ref().is_none_or(|m| m.iter().any(|m| m == *e)))
+            .map(|(i, e)| (i, e.to_string()))
+            .collect();
+        for (_, name) in &columns {
+            if !self.metrics.contains(name) {
+                self.metrics.push(name.to_string());
+            }
+        }
+
+        for line in lines {
+            let parts: Vec<&str> = line.split('\t').collect();
+            let distance = match parts.get(idx_distance).and_then(|e| e.parse::<i32>().ok()) {
+                Some(distance) => distance,
+                None => continue,
+            };
+            let tag = idx_tag.and_then(|i| parts.get(i)).unwrap_or(&"");
+            let sw_type = parts.get(idx_type).unwrap_or(&"");
+            let size = idx_size
+                .and_then(|i| parts.get(i))
+                .and_then(|e| e.parse::<i32>().ok());
+
+            let values_of = self
+                .groups
+                .entry((tag.to_string(), sw_type.to_string(), distance, size))
+                .or_default();
+            for (i, name) in &columns {
+                if let Some(value) = parts.get(*i).and_then(|e| e.parse::<f32>().ok()) {
+                    values_of.entry(name.to_string()).or_default().push(value);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn fmt_value(value: f32) -> String {
+    gams::round(value, 4).to_string()
+}
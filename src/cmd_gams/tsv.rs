@@ -46,24 +46,61 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     let mut raw_conn = gams::connect();
     let mut conn = gams::Conn::new(); // can't use one same `conn` inside an iter
 
+    // columns of windows vary with --rg-tag, --anno and targets of `gams sw`
+    if opt_pattern.starts_with("sw") {
+        let sws: Vec<gams::Sw> = conn
+            .get_scan_values(opt_pattern)
+            .iter()
+            .map(|el| serde_json::from_str(el).unwrap())
+            .collect();
+        gams::write_sws(&mut tsv_wtr, &sws)?;
+        return Ok(());
+    }
+
+    // the column of values of waves follows their --stat
+    let mut wave_column: Option<&str> = None;
+
     // scan
     let iter: redis::Iter<'_, String> = raw_conn.scan_match(opt_pattern).unwrap();
     for id in iter {
         if opt_pattern.starts_with("ctg") {
             let value: gams::Ctg = conn.get_ctg(&id);
             tsv_wtr.serialize(value).unwrap();
-        } else if opt_pattern.starts_with("feature") {
-            let json = conn.get_str(&id);
-            let value: gams::Feature = serde_json::from_str(&json).unwrap();
-            tsv_wtr.serialize(value).unwrap();
-        } else if opt_pattern.starts_with("rg") {
+        } else if let Some(group) = ["feature", "rg", "peak"]
+            .iter()
+            .find(|e| opt_pattern.starts_with(*e))
+        {
             let json = conn.get_str(&id);
-            let value: gams::Rg = serde_json::from_str(&json).unwrap();
-            tsv_wtr.serialize(value).unwrap();
-        } else if opt_pattern.starts_with("peak") {
-            let json = conn.get_str(&id);
-            let value: gams::Peak = serde_json::from_str(&json).unwrap();
-            tsv_wtr.serialize(value).unwrap();
+            gams::write_record(&mut tsv_wtr, group, &json)?;
+        } else if opt_pattern.starts_with("wave") {
+            // one row for each sliding window
+            let wave = conn.get_wave(id.trim_start_matches("wave:"));
+            let column = gams::stat_column(&wave.stat);
+            match wave_column {
+                None => {
+                    tsv_wtr.write_record(["range", column, "signal"])?;
+                    wave_column = Some(column);
+                }
+                Some(prev) if prev != column => {
+                    anyhow::bail!(
+                        "Waves of {} and {} can't be exported together",
+                        prev,
+                        column
+                    )
+                }
+                _ => {}
+            }
+
+            let ctg = conn.get_ctg(&wave.id);
+            let parent = intspan::IntSpan::from_pair(ctg.chr_start, ctg.chr_end);
+            let windows = gams::sliding(&parent, wave.size, wave.step);
+            for (i, window) in windows.iter().enumerate() {
+                tsv_wtr.write_record([
+                    format!("{}:{}", ctg.chr_id, window.runlist()),
+                    wave.values[i].to_string(),
+                    wave.signals[i].to_string(),
+                ])?;
+            }
         }
     }
 
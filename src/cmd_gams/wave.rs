@@ -1,5 +1,5 @@
 use clap::*;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::Write;
 
 // Create clap subcommand arguments
@@ -11,12 +11,33 @@ pub fn make_subcommand() -> Command {
 * --step and --lag should be adjust simultaneously
     * step * lag = 1000 for 1000 bp regions
 
+* --stat selects the statistic of sliding windows
+    * gc: GC-content, Ns count as non-GC bases
+    * gc-skew / at-skew: (G - C) / (G + C) and (A - T) / (A + T), switches mark replication origins
+    * cpg: observed/expected ratio of CpG dinucleotides
+    * entropy: Shannon entropy of A, C, G and T, in bits
+    * gibbs: free energy of the DNA duplex, kcal/mol, see --temp and --salt
+        * Windows with Ns have no free energies and take the mean of other windows of the ctg
+    * The second column of outputs and the series stored by --db follow --stat
+
+* --method selects the peak detector, all of them use --lag and --threshold
+    * zscore: z-scores against a moving window, --influence applies
+    * cwt: continuous wavelet transform with Ricker wavelets of widths up to lag
+    * cusum: two-sided CUSUM of z-scores with a drift of threshold / 2
+    * hmm: two-state HMM, background N(0, 1) and peak N(0, threshold^2) on z-scores
+
 * Crests and troughs are merge separatedly
 * Merged peaks would be like "I(+):11551-11740"
 
+* --summary N reports peaks in bins of N kb along each ctg
+    * `range  crests  troughs  wave_length  amplitude`
+    * Peaks belong to the bins containing their starts
+    * wave_length and amplitude are means of distances and differences of values to the next peaks
+* --db stores the series of --stat of each ctg, `gams tsv --scan 'wave:*'` exports them
+
 * Running in parallel mode will active 1 reader, 1 writer (the main thread)
   and the corresponding number of workers
-    * The order of output may differ from serial mode
+    * The writer reorders results, so the output is identical to serial mode
 
 "###,
         )
@@ -41,6 +62,50 @@ pub fn make_subcommand() -> Command {
                 .default_value("10")
                 .value_parser(value_parser!(i32))
         )
+        .arg(
+            Arg::new("stat")
+                .long("stat")
+                .num_args(1)
+                .default_value("gc")
+                .value_parser([
+                    builder::PossibleValue::new("gc"),
+                    builder::PossibleValue::new("gc-skew"),
+                    builder::PossibleValue::new("at-skew"),
+                    builder::PossibleValue::new("cpg"),
+                    builder::PossibleValue::new("entropy"),
+                    builder::PossibleValue::new("gibbs"),
+                ])
+                .help("The statistic of sliding windows"),
+        )
+        .arg(
+            Arg::new("temp")
+                .long("temp")
+                .num_args(1)
+                .value_parser(value_parser!(f32))
+                .default_value("37.0")
+                .help("Temperature of --stat gibbs, in degree centigrade"),
+        )
+        .arg(
+            Arg::new("salt")
+                .long("salt")
+                .num_args(1)
+                .value_parser(value_parser!(f32))
+                .default_value("1.0")
+                .help("Salt concentration of --stat gibbs, Na+ in M"),
+        )
+        .arg(
+            Arg::new("method")
+                .long("method")
+                .num_args(1)
+                .default_value("zscore")
+                .value_parser([
+                    builder::PossibleValue::new("zscore"),
+                    builder::PossibleValue::new("cwt"),
+                    builder::PossibleValue::new("cusum"),
+                    builder::PossibleValue::new("hmm"),
+                ])
+                .help("The peak detection algorithm"),
+        )
         .arg(
             Arg::new("lag")
                 .long("lag")
@@ -71,6 +136,20 @@ pub fn make_subcommand() -> Command {
                 .action(ArgAction::SetTrue)
                 .help("Write sliding windows with signals, not just peaks"),
         )
+        .arg(
+            Arg::new("summary")
+                .long("summary")
+                .num_args(1)
+                .default_value("0")
+                .value_parser(value_parser!(i32))
+                .help("Summarize peaks in bins of this many kb, 0 for no summaries"),
+        )
+        .arg(
+            Arg::new("db")
+                .long("db")
+                .action(ArgAction::SetTrue)
+                .help("Store values and signals of sliding windows as `wave:{ctg_id}`"),
+        )
         .arg(
             Arg::new("coverage")
                 .long("coverage")
@@ -102,7 +181,7 @@ pub fn make_subcommand() -> Command {
 // command implementation
 pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
     // redis connection
-    let ctgs: Vec<gams::Ctg> = {
+    let mut ctgs: Vec<gams::Ctg> = {
         let mut conn = gams::Conn::new();
         let jsons: Vec<String> = conn.get_scan_values(args.get_one::<String>("ctg").unwrap());
         jsons
@@ -110,6 +189,8 @@ pub fn execute(args: &ArgMatches) -> anyhow::Result<()> {
             .map(|el| serde_json::from_str(el).unwrap())
             .collect()
     };
+    // SCAN returns keys in no particular order, sort them as `gams sw` does
+    ctgs.sort_by(|a, b| a.id.cmp(&b.id));
 
     eprintln!("{} contigs to be processed", ctgs.len());
 
@@ -124,11 +205,17 @@ fn proc_ctg(ctg: &gams::Ctg, args: &ArgMatches) -> String {
     //----------------------------
     let opt_size = *args.get_one::<i32>("size").unwrap();
     let opt_step = *args.get_one::<i32>("step").unwrap();
+    let opt_stat = args.get_one::<String>("stat").unwrap();
+    let opt_temp = *args.get_one::<f32>("temp").unwrap();
+    let opt_salt = *args.get_one::<f32>("salt").unwrap();
+    let opt_method = args.get_one::<String>("method").unwrap();
     let opt_lag = *args.get_one::<usize>("lag").unwrap();
     let opt_threshold = *args.get_one::<f32>("threshold").unwrap();
     let opt_influence = *args.get_one::<f32>("influence").unwrap();
     let opt_coverage = *args.get_one::<f32>("coverage").unwrap();
     let is_signal = args.get_flag("signal");
+    let opt_summary = *args.get_one::<i32>("summary").unwrap();
+    let is_db = args.get_flag("db");
 
     // redis connection
     let mut conn = gams::Conn::new();
@@ -140,19 +227,60 @@ fn proc_ctg(ctg: &gams::Ctg, args: &ArgMatches) -> String {
 
     let ctg_seq: String = conn.get_seq(&ctg.id);
 
-    let mut gcs: Vec<f32> = Vec::with_capacity(windows.len());
+    // values of --stat, GC-contents by default
+    let counts = gams::BaseCount::new(&ctg_seq);
+    let dg = gams::DeltaG::from(opt_temp, opt_salt);
+    let mut values: Vec<Option<f32>> = Vec::with_capacity(windows.len());
     for window in &windows {
         // converted to ctg index
         let from = parent.index(window.min()) as usize;
         let to = parent.index(window.max()) as usize;
 
         // from <= x < to, zero-based
-        let subseq = ctg_seq.get((from - 1)..(to)).unwrap().bytes();
-        let gc_content = bio::seq_analysis::gc::gc_content(subseq);
-        gcs.push(gc_content);
+        let subseq = ctg_seq.get((from - 1)..(to)).unwrap();
+        let value = match opt_stat.as_str() {
+            "gc-skew" => Some(gams::gc_skew(subseq.as_bytes())),
+            "at-skew" => Some(gams::at_skew(subseq.as_bytes())),
+            "cpg" => Some(gams::cpg_oe(subseq.as_bytes())),
+            "entropy" => Some(gams::shannon_entropy(subseq.as_bytes())),
+            "gibbs" => dg.polymer(subseq),
+            _ => Some(counts.gc_content(from, to)),
+        };
+        values.push(match opt_stat.as_str() {
+            "gc" => value,
+            _ => value.map(|v| gams::round(v, 4)),
+        });
     }
 
-    let signals = gams::thresholding_algo(&gcs, opt_lag, opt_threshold, opt_influence);
+    // only gibbs leaves windows with Ns empty, they take the mean of other windows
+    let series: Vec<f32> = {
+        let others: Vec<f32> = values.iter().flatten().copied().collect();
+        let avg = if others.is_empty() {
+            0.
+        } else {
+            gams::round(gams::mean(&others), 4)
+        };
+        values.iter().map(|e| e.unwrap_or(avg)).collect()
+    };
+
+    let signals = match opt_method.as_str() {
+        "cwt" => gams::cwt_algo(&series, opt_lag, opt_threshold),
+        "cusum" => gams::cusum_algo(&series, opt_lag, opt_threshold),
+        "hmm" => gams::hmm_algo(&series, opt_lag, opt_threshold),
+        _ => gams::thresholding_algo(&series, opt_lag, opt_threshold, opt_influence),
+    };
+
+    if is_db {
+        let wave = gams::Wave {
+            id: ctg.id.clone(),
+            size: opt_size,
+            step: opt_step,
+            stat: opt_stat.to_string(),
+            values: series.clone(),
+            signals: signals.clone(),
+        };
+        conn.insert_wave(&ctg.id, &wave);
+    }
 
     let mut out_string = "".to_string();
     if is_signal {
@@ -161,54 +289,142 @@ fn proc_ctg(ctg: &gams::Ctg, args: &ArgMatches) -> String {
                 "{}:{}\t{}\t{}\n",
                 ctg.chr_id,
                 windows[i].runlist(),
-                gcs[i],
+                series[i],
                 signals[i],
             )
             .as_str();
         }
     } else {
-        let mut merge_of = HashMap::new();
-        {
-            let crests: Vec<intspan::IntSpan> = windows
-                .iter()
-                .enumerate()
-                .filter(|(i, _)| signals[*i] == 1)
-                .map(|(_, el)| el.clone())
-                .collect();
-            merge_of.extend(merge_ints(crests, opt_coverage));
-
-            let troughs: Vec<intspan::IntSpan> = windows
-                .iter()
-                .enumerate()
-                .filter(|(i, _)| signals[*i] == -1)
-                .map(|(_, el)| el.clone())
-                .collect();
-            merge_of.extend(merge_ints(troughs, opt_coverage));
+        let peaks = to_peaks(&windows, &series, &signals, opt_coverage);
+
+        if opt_summary > 0 {
+            out_string += &summarize(ctg, &parent, &peaks, opt_summary);
+        } else {
+            for (ints, value, signal, is_merged) in &peaks {
+                let strand = if *is_merged { "(+)" } else { "" };
+                out_string += format!(
+                    "{}{}:{}\t{}\t{}\n",
+                    ctg.chr_id,
+                    strand,
+                    ints.runlist(),
+                    value,
+                    signal,
+                )
+                .as_str();
+            }
         }
+    }
 
-        // outputs
-        let mut seen = HashSet::new();
-        for i in 0..windows.len() {
-            if signals[i] == 0 {
-                continue;
+    out_string
+}
+
+/// (IntSpan, value, signal, is_merged)
+type WavePeak = (intspan::IntSpan, f32, i32, bool);
+
+/// Crests and troughs, in the order of positions
+///
+/// Overlapping windows with the same signal are merged, and the merged peak carries the value
+/// of its first member
+fn to_peaks(
+    windows: &[intspan::IntSpan],
+    values: &[f32],
+    signals: &[i32],
+    opt_coverage: f32,
+) -> Vec<WavePeak> {
+    let mut merge_of = HashMap::new();
+    {
+        let crests: Vec<intspan::IntSpan> = windows
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| signals[*i] == 1)
+            .map(|(_, el)| el.clone())
+            .collect();
+        merge_of.extend(merge_ints(crests, opt_coverage));
+
+        let troughs: Vec<intspan::IntSpan> = windows
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| signals[*i] == -1)
+            .map(|(_, el)| el.clone())
+            .collect();
+        merge_of.extend(merge_ints(troughs, opt_coverage));
+    }
+
+    let mut peaks = vec![];
+    let mut seen = HashSet::new();
+    for i in 0..windows.len() {
+        if signals[i] == 0 {
+            continue;
+        }
+
+        let runlist = windows[i].runlist();
+        if merge_of.contains_key(&runlist) {
+            // merged window
+            // value and signal of the first member
+            let merge = merge_of.get(&runlist).unwrap();
+            if !seen.contains(merge) {
+                peaks.push((intspan::IntSpan::from(merge), values[i], signals[i], true));
+                seen.insert(merge.clone());
             }
+        } else {
+            peaks.push((windows[i].clone(), values[i], signals[i], false));
+        }
+    }
 
-            let runlist = windows[i].runlist();
-            if merge_of.contains_key(&runlist) {
-                // merged window
-                // gc and signal of the first member
-                let merge = merge_of.get(&runlist).unwrap();
-                if !seen.contains(merge) {
-                    out_string +=
-                        format!("{}(+):{}\t{}\t{}\n", ctg.chr_id, merge, gcs[i], signals[i],)
-                            .as_str();
-                    seen.insert(merge.clone());
-                }
+    peaks
+}
+
+/// Peaks are counted in bins of `kb` kb along the ctg, by their starts
+///
+/// Wave lengths and amplitudes are measured from each peak to the next one, like `gams peak`
+fn summarize(ctg: &gams::Ctg, parent: &intspan::IntSpan, peaks: &[WavePeak], kb: i32) -> String {
+    let bin_size = kb * 1000;
+
+    let mut out_string = "".to_string();
+    let mut bin_start = 1;
+    while bin_start <= parent.size() {
+        let bin_end = (bin_start + bin_size - 1).min(parent.size());
+        let bin = parent.slice(bin_start, bin_end);
+
+        let mut crests = 0;
+        let mut troughs = 0;
+        let mut wave_lengths: Vec<f32> = vec![];
+        let mut amplitudes: Vec<f32> = vec![];
+        for (i, (ints, value, signal, _)) in peaks.iter().enumerate() {
+            if !bin.contains(ints.min()) {
+                continue;
+            }
+            if *signal == 1 {
+                crests += 1;
             } else {
-                out_string +=
-                    format!("{}:{}\t{}\t{}\n", ctg.chr_id, runlist, gcs[i], signals[i],).as_str();
+                troughs += 1;
+            }
+
+            if let Some((next, next_value, _, _)) = peaks.get(i + 1) {
+                wave_lengths.push((next.min() - ints.max() + 1) as f32);
+                amplitudes.push((next_value - value).abs());
             }
         }
+
+        let fmt_mean = |values: &[f32]| {
+            if values.is_empty() {
+                "".to_string()
+            } else {
+                gams::round(gams::mean(values), 4).to_string()
+            }
+        };
+        out_string += format!(
+            "{}:{}\t{}\t{}\t{}\t{}\n",
+            ctg.chr_id,
+            bin.runlist(),
+            crests,
+            troughs,
+            fmt_mean(&wave_lengths),
+            fmt_mean(&amplitudes),
+        )
+        .as_str();
+
+        bin_start = bin_end + 1;
     }
 
     out_string
@@ -252,7 +468,7 @@ fn merge_ints(peaks: Vec<intspan::IntSpan>, opt_coverage: f32) -> HashMap<String
 }
 
 // Adopt from https://rust-lang-nursery.github.io/rust-cookbook/concurrency/threads.html#create-a-parallel-pipeline
-fn proc_ctg_p(ctgs: &Vec<gams::Ctg>, args: &ArgMatches) -> anyhow::Result<()> {
+fn proc_ctg_p(ctgs: &[gams::Ctg], args: &ArgMatches) -> anyhow::Result<()> {
     //----------------------------
     // Args
     //----------------------------
@@ -260,23 +476,31 @@ fn proc_ctg_p(ctgs: &Vec<gams::Ctg>, args: &ArgMatches) -> anyhow::Result<()> {
     let opt_parallel = *args.get_one::<usize>("parallel").unwrap();
 
     // headers
-    writer.write_fmt(format_args!(
-        "{}\t{}\t{}\n",
-        "#range", "gc_content", "signal"
-    ))?;
+    if !args.get_flag("signal") && *args.get_one::<i32>("summary").unwrap() > 0 {
+        writer.write_fmt(format_args!(
+            "{}\t{}\t{}\t{}\t{}\n",
+            "#range", "crests", "troughs", "wave_length", "amplitude"
+        ))?;
+    } else {
+        let column = gams::stat_column(args.get_one::<String>("stat").unwrap());
+        writer.write_fmt(format_args!("{}\t{}\t{}\n", "#range", column, "signal"))?;
+    }
 
-    // Channel 1 - Contigs
-    let (snd1, rcv1) = crossbeam::channel::bounded::<gams::Ctg>(10);
+    // Channel 1 - Contigs, with their indexes
+    let (snd1, rcv1) = crossbeam::channel::bounded::<(usize, gams::Ctg)>(10);
     // Channel 2 - Results
-    let (snd2, rcv2) = crossbeam::channel::bounded::<String>(10);
+    let (snd2, rcv2) = crossbeam::channel::bounded::<(usize, String)>(10);
+    // Channel 3 - Tickets, limits contigs in flight and thus the reorder buffer
+    let (snd3, rcv3) = crossbeam::channel::bounded::<()>(opt_parallel * 4);
 
     crossbeam::scope(|s| {
         //----------------------------
         // Reader thread
         //----------------------------
         s.spawn(|_| {
-            for ctg in ctgs {
-                snd1.send(ctg.clone()).unwrap();
+            for (i, ctg) in ctgs.iter().enumerate() {
+                snd3.send(()).unwrap();
+                snd1.send((i, ctg.clone())).unwrap();
             }
             // Close the channel - this is necessary to exit the for-loop in the worker
             drop(snd1);
@@ -291,9 +515,9 @@ fn proc_ctg_p(ctgs: &Vec<gams::Ctg>, args: &ArgMatches) -> anyhow::Result<()> {
             // Spawn workers in separate threads
             s.spawn(move |_| {
                 // Receive until channel closes
-                for ctg in recvr.iter() {
+                for (i, ctg) in recvr.iter() {
                     let out_string = proc_ctg(&ctg, args);
-                    sendr.send(out_string).unwrap();
+                    sendr.send((i, out_string)).unwrap();
                 }
             });
         }
@@ -303,8 +527,16 @@ fn proc_ctg_p(ctgs: &Vec<gams::Ctg>, args: &ArgMatches) -> anyhow::Result<()> {
         //----------------------------
         // Writer (main) thread
         //----------------------------
-        for out_string in rcv2.iter() {
-            writer.write_all(out_string.as_ref()).unwrap();
+        // results are written in the order of contigs
+        let mut pending: BTreeMap<usize, String> = BTreeMap::new();
+        let mut next = 0;
+        for (i, out_string) in rcv2.iter() {
+            pending.insert(i, out_string);
+            while let Some(out_string) = pending.remove(&next) {
+                writer.write_all(out_string.as_ref()).unwrap();
+                rcv3.recv().unwrap();
+                next += 1;
+            }
         }
     })
     .unwrap();
@@ -14,24 +14,30 @@ fn main() -> anyhow::Result<()> {
         .subcommand(cmd_gams::env::make_subcommand())
         .subcommand(cmd_gams::status::make_subcommand())
         .subcommand(cmd_gams::gen::make_subcommand())
+        .subcommand(cmd_gams::alias::make_subcommand())
         .subcommand(cmd_gams::locate::make_subcommand())
         .subcommand(cmd_gams::rg::make_subcommand())
         .subcommand(cmd_gams::clear::make_subcommand())
         .subcommand(cmd_gams::feature::make_subcommand())
+        .subcommand(cmd_gams::edit::make_subcommand())
         .subcommand(cmd_gams::sw::make_subcommand())
+        .subcommand(cmd_gams::swstat::make_subcommand())
         .subcommand(cmd_gams::anno::make_subcommand())
         .subcommand(cmd_gams::wave::make_subcommand())
         .subcommand(cmd_gams::peak::make_subcommand())
         .subcommand(cmd_gams::tsv::make_subcommand())
+        .subcommand(cmd_gams::query::make_subcommand())
         .after_help(
             r###"
 Subcommand groups:
 
 *
 
-* Reading: feature / rg / peak
+* Reading: alias / feature / rg / peak
 
-* Writing: tsv / sw
+* Editing: edit / clear
+
+* Writing: tsv / query / sw / swstat
 
 * Per Ctg: anno / wave / locate
 
@@ -43,15 +49,19 @@ Subcommand groups:
         Some(("env", sub_matches)) => cmd_gams::env::execute(sub_matches),
         Some(("status", sub_matches)) => cmd_gams::status::execute(sub_matches),
         Some(("gen", sub_matches)) => cmd_gams::gen::execute(sub_matches),
+        Some(("alias", sub_matches)) => cmd_gams::alias::execute(sub_matches),
         Some(("locate", sub_matches)) => cmd_gams::locate::execute(sub_matches),
         Some(("rg", sub_matches)) => cmd_gams::rg::execute(sub_matches),
         Some(("clear", sub_matches)) => cmd_gams::clear::execute(sub_matches),
         Some(("feature", sub_matches)) => cmd_gams::feature::execute(sub_matches),
+        Some(("edit", sub_matches)) => cmd_gams::edit::execute(sub_matches),
         Some(("sw", sub_matches)) => cmd_gams::sw::execute(sub_matches),
+        Some(("swstat", sub_matches)) => cmd_gams::swstat::execute(sub_matches),
         Some(("anno", sub_matches)) => cmd_gams::anno::execute(sub_matches),
         Some(("wave", sub_matches)) => cmd_gams::wave::execute(sub_matches),
         Some(("peak", sub_matches)) => cmd_gams::peak::execute(sub_matches),
         Some(("tsv", sub_matches)) => cmd_gams::tsv::execute(sub_matches),
+        Some(("query", sub_matches)) => cmd_gams::query::execute(sub_matches),
         _ => unreachable!(),
     }
     .unwrap();
@@ -59,10 +69,8 @@ Subcommand groups:
     Ok(())
 }
 
-// TODO: sliding windows of waves
 // TODO: `gams count`
 // TODO: ctgs should be slightly overlapped with each other, 500 bp?
-// TODO: `gams swstat` action
 // TODO:
 //  gams-stat executes sql to generate .tsv
 //  rust_xlsxwriter read .tsv, creates charts and writes .xlsx
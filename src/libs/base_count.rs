@@ -0,0 +1,91 @@
+//! `BaseCount` counts bases of any region of a sequence in O(1)
+//!
+//! # SYNOPSIS
+//!
+//! ```
+//! use gams::BaseCount;
+//!
+//! let counts = BaseCount::new("ACGTnnacgt");
+//! assert_eq!(counts.gc_content(1, 4), 0.5);
+//! assert_eq!(counts.n_count(1, 10), 2);
+//! assert_eq!(counts.mask_count(5, 10), 6);
+//! ```
+//!
+//! # ATTRIBUTES
+//! `gc`: cumulative counts of G and C, case-insensitive
+//! `n`: cumulative counts of N, case-insensitive
+//! `mask`: cumulative counts of soft-masked, lowercase, bases
+//!
+//! Positions are 1-based and inclusive, like indexes of `IntSpan`
+
+#[derive(Default, Clone)]
+pub struct BaseCount {
+    gc: Vec<u32>,
+    n: Vec<u32>,
+    mask: Vec<u32>,
+}
+
+impl BaseCount {
+    /// Cumulative counts are built in one pass
+    ///
+    /// ```
+    /// # use gams::BaseCount;
+    /// let counts = BaseCount::new("");
+    /// assert_eq!(counts.len(), 0);
+    /// assert!(counts.is_empty());
+    /// ```
+    pub fn new(seq: &str) -> Self {
+        let len = seq.len();
+        let mut gc = Vec::with_capacity(len + 1);
+        let mut n = Vec::with_capacity(len + 1);
+        let mut mask = Vec::with_capacity(len + 1);
+        gc.push(0);
+        n.push(0);
+        mask.push(0);
+
+        for (i, base) in seq.bytes().enumerate() {
+            gc.push(gc[i] + matches!(base, b'G' | b'C' | b'g' | b'c') as u32);
+            n.push(n[i] + matches!(base, b'N' | b'n') as u32);
+            mask.push(mask[i] + base.is_ascii_lowercase() as u32);
+        }
+
+        Self { gc, n, mask }
+    }
+
+    pub fn len(&self) -> usize {
+        self.gc.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn gc_count(&self, from: usize, to: usize) -> u32 {
+        self.gc[to] - self.gc[from - 1]
+    }
+
+    pub fn n_count(&self, from: usize, to: usize) -> u32 {
+        self.n[to] - self.n[from - 1]
+    }
+
+    pub fn mask_count(&self, from: usize, to: usize) -> u32 {
+        self.mask[to] - self.mask[from - 1]
+    }
+
+    /// GC-content of `from..=to`, Ns are counted in the length like `bio::seq_analysis::gc`
+    ///
+    /// ```
+    /// # use gams::BaseCount;
+    /// let seq = "ATGCNNgcat";
+    /// let counts = BaseCount::new(seq);
+    /// assert_eq!(counts.gc_content(1, 10), 0.4);
+    /// assert_eq!(counts.gc_content(3, 8), 0.6666667);
+    /// assert_eq!(
+    ///     counts.gc_content(3, 8),
+    ///     bio::seq_analysis::gc::gc_content(seq[2..8].bytes())
+    /// );
+    /// ```
+    pub fn gc_content(&self, from: usize, to: usize) -> f32 {
+        self.gc_count(from, to) as f32 / (to - from + 1) as f32
+    }
+}
@@ -13,18 +13,40 @@ pub struct Ctg {
     pub length: i32,
 }
 
+/// Fields after tag are optional, mostly from GFF3 gene models
+/// parent and gff_id are IDs in the GFF3 file
 #[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Feature {
     pub id: String,
     pub range: String,
     pub length: i32,
     pub tag: String,
+    pub strand: Option<String>,
+    pub feature_type: Option<String>,
+    pub name: Option<String>,
+    pub biotype: Option<String>,
+    pub gff_id: Option<String>,
+    pub parent: Option<String>,
 }
 
+/// rgs stored before --tag was added are read with the default tag
+///
+/// ```
+/// let rg: gams::Rg = serde_json::from_str(r#"{"id":"rg:ctg:I:1:1","range":"I:1-100"}"#).unwrap();
+/// assert_eq!(rg.tag, "rg");
+/// assert_eq!(rg.value, None);
+/// ```
 #[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Rg {
     pub id: String,
     pub range: String,
+    #[serde(default = "default_rg_tag")]
+    pub tag: String,
+    pub value: Option<f32>,
+}
+
+fn default_rg_tag() -> String {
+    "rg".to_string()
 }
 
 #[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -42,25 +64,132 @@ pub struct Peak {
     pub right_signal: Option<String>,
 }
 
-#[derive(Default, Clone, Debug)]
+/// Values of --stat and signals of sliding windows along a ctg, see `gams wave --db`
+///
+/// Windows are regenerated by `sliding(ctg, size, step)`
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Wave {
+    pub id: String,
+    pub size: i32,
+    pub step: i32,
+    /// --stat of `gams wave`, e.g. gc or gibbs
+    pub stat: String,
+    pub values: Vec<f32>,
+    pub signals: Vec<i32>,
+}
+
+/// Per-tag and --anno columns carry their names, so stored windows can be exported
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Sw {
     pub id: String,
     pub range: String,
+    /// Tag of the feature, or `peak`
+    pub tag: String,
+    #[serde(rename = "type")]
     pub sw_type: String,
     pub distance: i32,
+    /// --size the window is built with, not its width, as intact `M`, `B` and --log windows differ
+    pub base_size: i32,
     pub gc_content: Option<f32>,
     pub gc_mean: Option<f32>,
     pub gc_stddev: Option<f32>,
     pub gc_cv: Option<f32>,
+    pub gibbs: Option<f32>,
+    pub gibbs_mean: Option<f32>,
+    pub gibbs_stddev: Option<f32>,
     pub rg_count: Option<i32>,
+    /// rgs per kb
+    pub rg_density: Option<f32>,
+    pub rg_value: Option<f32>,
+    /// Tags of tag_counts and tag_values
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tag_names: Vec<String>,
+    /// Counts of rgs of each tag
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tag_counts: Vec<i32>,
+    /// Aggregated values of rgs of each tag, empty when values aren't aggregated
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tag_values: Vec<Option<f32>>,
+    /// Signal of the peak, crest or trough, only for windows around peaks
+    pub peak_signal: Option<String>,
+    pub peak_gc: Option<f32>,
+    pub left_wave_length: Option<i32>,
+    pub right_wave_length: Option<i32>,
+    /// Names of --anno
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub anno_names: Vec<String>,
+    /// Proportions covered by runlists of --anno
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub anno_props: Vec<f32>,
+}
+
+impl Sw {
+    /// Column names of the Display output
+    ///
+    /// ```
+    /// let sw = gams::Sw {
+    ///     tag_names: vec!["spo11".to_string()],
+    ///     tag_counts: vec![1],
+    ///     anno_names: vec!["cds".to_string()],
+    ///     anno_props: vec![0.5],
+    ///     ..Default::default()
+    /// };
+    /// let headers = sw.headers();
+    /// assert_eq!(headers.len(), sw.to_string().split('\t').count());
+    /// assert_eq!(headers[2], "tag");
+    /// assert_eq!(headers[16..], ["spo11_count", "cdsProp"]);
+    /// ```
+    pub fn headers(&self) -> Vec<String> {
+        let mut headers: Vec<String> = [
+            "id",
+            "range",
+            "tag",
+            "type",
+            "distance",
+            "base_size",
+            "gc_content",
+            "gc_mean",
+            "gc_stddev",
+            "gc_cv",
+            "gibbs",
+            "gibbs_mean",
+            "gibbs_stddev",
+            "rg_count",
+            "rg_density",
+            "rg_value",
+        ]
+        .iter()
+        .map(|e| e.to_string())
+        .collect();
+        for (i, name) in self.tag_names.iter().enumerate() {
+            headers.push(format!("{}_count", name));
+            if self.tag_values.get(i).is_some() {
+                headers.push(format!("{}_value", name));
+            }
+        }
+        if self.peak_signal.is_some() {
+            for header in [
+                "peak_signal",
+                "peak_gc",
+                "left_wave_length",
+                "right_wave_length",
+            ] {
+                headers.push(header.to_string());
+            }
+        }
+        for name in &self.anno_names {
+            headers.push(format!("{}Prop", name));
+        }
+        headers
+    }
 }
 
 impl fmt::Display for Sw {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let res_gc = if self.gc_content.is_some() {
+        let res_gc = if let Some(gc_content) = self.gc_content {
             format!(
                 "{}\t{}\t{}\t{}",
-                self.gc_content.unwrap(),
+                gc_content,
                 self.gc_mean.unwrap(),
                 self.gc_stddev.unwrap(),
                 self.gc_cv.unwrap()
@@ -68,16 +197,62 @@ impl fmt::Display for Sw {
         } else {
             "\t\t\t".to_string() // empty fields
         };
-        let res_rg = if self.rg_count.is_some() {
-            format!("{}", self.rg_count.unwrap())
+        // windows with Ns have no free energies
+        let res_gibbs = [self.gibbs, self.gibbs_mean, self.gibbs_stddev]
+            .iter()
+            .map(|v| v.map(|v| v.to_string()).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join("\t");
+        let res_rg = if let Some(rg_count) = self.rg_count {
+            format!("{}\t{}", rg_count, self.rg_density.unwrap())
+        } else {
+            "\t".to_string() // empty fields
+        };
+        let res_value = if let Some(rg_value) = self.rg_value {
+            format!("{}", rg_value)
         } else {
             "".to_string()
         };
         write!(
             f,
-            "{}\t{}\t{}\t{}\t{}\t{}",
-            self.id, self.range, self.sw_type, self.distance, res_gc, res_rg
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.id,
+            self.range,
+            self.tag,
+            self.sw_type,
+            self.distance,
+            self.base_size,
+            res_gc,
+            res_gibbs,
+            res_rg,
+            res_value
         )?;
+        for (i, tag_count) in self.tag_counts.iter().enumerate() {
+            write!(f, "\t{}", tag_count)?;
+            if let Some(tag_value) = self.tag_values.get(i) {
+                match tag_value {
+                    Some(v) => write!(f, "\t{}", v)?,
+                    None => write!(f, "\t")?,
+                }
+            }
+        }
+        if let Some(peak_signal) = &self.peak_signal {
+            write!(
+                f,
+                "\t{}\t{}\t{}\t{}",
+                peak_signal,
+                self.peak_gc.map(|v| v.to_string()).unwrap_or_default(),
+                self.left_wave_length
+                    .map(|v| v.to_string())
+                    .unwrap_or_default(),
+                self.right_wave_length
+                    .map(|v| v.to_string())
+                    .unwrap_or_default(),
+            )?;
+        }
+        for anno_prop in &self.anno_props {
+            write!(f, "\t{:.4}", anno_prop)?;
+        }
         Ok(())
     }
 }
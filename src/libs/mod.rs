@@ -1,3 +1,4 @@
+pub mod base_count;
 pub mod data;
 pub mod delta_g;
 pub mod redis;
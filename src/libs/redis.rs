@@ -67,6 +67,12 @@ pub struct Conn {
     size: usize,
 }
 
+impl Default for Conn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// INTERFACE: Redis connection
 /// Three basic data types: str, bin and sn
 /// Wrapped data: ctg and seq
@@ -124,6 +130,17 @@ impl Conn {
         self.conn().get(key).unwrap_or(0)
     }
 
+    pub fn set_sn(&mut self, key: &str, n: i32) {
+        self.conn().set(key, n).unwrap()
+    }
+
+    pub fn del_keys(&mut self, keys: &[String]) {
+        if keys.is_empty() {
+            return;
+        }
+        self.conn().del(keys).unwrap()
+    }
+
     pub fn insert_ctg(&mut self, ctg_id: &str, ctg: &crate::Ctg) {
         let json = serde_json::to_string(ctg).unwrap();
         self.insert_str(ctg_id, &json);
@@ -144,6 +161,16 @@ impl Conn {
         let s = decode_gz(&seq_bytes).unwrap();
         String::from_utf8(s).unwrap()
     }
+
+    pub fn insert_wave(&mut self, ctg_id: &str, wave: &crate::Wave) {
+        let serialized = bincode::serialize(wave).unwrap();
+        self.insert_bin(&format!("wave:{ctg_id}"), &serialized)
+    }
+
+    pub fn get_wave(&mut self, ctg_id: &str) -> crate::Wave {
+        let bytes: Vec<u8> = self.get_bin(&format!("wave:{}", ctg_id));
+        bincode::deserialize(&bytes).unwrap()
+    }
 }
 
 fn encode_gz(seq: &[u8]) -> anyhow::Result<Vec<u8>> {
@@ -171,6 +198,21 @@ impl Conn {
         serde_json::from_str(&json).unwrap()
     }
 
+    /// chr_id => length
+    pub fn get_chr_len(&mut self) -> BTreeMap<String, usize> {
+        let json = self.get_str("top:chr_len");
+        serde_json::from_str(&json).unwrap()
+    }
+
+    /// alias => chr_id, empty if no aliases were stored
+    pub fn get_chr_alias(&mut self) -> BTreeMap<String, String> {
+        let json: Option<String> = self.conn().get("top:chr_alias").unwrap();
+        match json {
+            Some(json) => serde_json::from_str(&json).unwrap(),
+            None => BTreeMap::new(),
+        }
+    }
+
     /// generated from cnt:ctg:
     pub fn get_vec_ctg(&mut self, chr_id: &str) -> Vec<String> {
         let key = format!("cnt:ctg:{}", chr_id);
@@ -214,8 +256,8 @@ impl Conn {
 
     /// BTreeMap<ctg_id, Ctg>
     pub fn get_bundle_ctg(&mut self, chr_id: Option<&str>) -> BTreeMap<String, crate::Ctg> {
-        let chrs: Vec<String> = if chr_id.is_some() {
-            vec![chr_id.unwrap().to_string()]
+        let chrs: Vec<String> = if let Some(chr_id) = chr_id {
+            vec![chr_id.to_string()]
         } else {
             self.get_vec_chr()
         };
@@ -273,6 +315,8 @@ impl Conn {
     }
 
     /// This index helps counting overlaps
+    ///
+    /// `idx:rg:{ctg_id}` for all rgs, and `idx:rg:{ctg_id}:{tag}` for rgs of each tag
     pub fn build_idx_rg(&mut self) {
         let chrs: Vec<String> = self.get_vec_chr();
         for chr_id in chrs.iter() {
@@ -285,27 +329,38 @@ impl Conn {
                     .map(|el| serde_json::from_str(el).unwrap())
                     .collect();
 
+                // tag => [Iv]
+                let mut ivs_of: BTreeMap<String, Vec<Iv>> = BTreeMap::new();
                 let mut ivs: Vec<Iv> = vec![];
                 for rg in &rgs {
                     let range = intspan::Range::from_str(&rg.range);
+                    // we don't need find rg, values are kept for aggregations
                     let iv = Iv {
                         start: range.start as u32,
                         stop: range.end as u32 + 1,
-                        val: "".to_string(), // we don't need find rg
+                        val: rg.value.map(|v| v.to_string()).unwrap_or_default(),
                     };
+                    ivs_of.entry(rg.tag.clone()).or_default().push(iv.clone());
                     ivs.push(iv);
                 }
 
                 let lapper = Lapper::new(ivs);
                 let serialized = bincode::serialize(&lapper).unwrap();
-
                 self.insert_bin(&format!("idx:rg:{ctg_id}"), &serialized);
+
+                for (tag, ivs) in ivs_of {
+                    let lapper = Lapper::new(ivs);
+                    let serialized = bincode::serialize(&lapper).unwrap();
+                    self.insert_bin(&format!("idx:rg:{ctg_id}:{tag}"), &serialized);
+                }
             }
         }
     }
 
     /// ctg_id => Lapper
-    pub fn get_idx_rg(&mut self) -> BTreeMap<String, Lapper<u32, String>> {
+    ///
+    /// All rgs, or rgs of the tag
+    pub fn get_idx_rg(&mut self, tag: Option<&str>) -> BTreeMap<String, Lapper<u32, String>> {
         let mut lapper_of: BTreeMap<String, Lapper<u32, String>> = BTreeMap::new();
 
         let chrs: Vec<String> = self.get_vec_chr();
@@ -313,15 +368,26 @@ impl Conn {
             let ctgs: Vec<String> = self.get_vec_cnt("ctg", chr_id);
 
             for ctg_id in &ctgs {
-                let bytes: Vec<u8> = self.get_bin(&format!("idx:rg:{}", ctg_id));
-                let lapper: Lapper<u32, String> = bincode::deserialize(&bytes).unwrap();
-
+                let lapper = self.get_idx_rg_ctg(ctg_id, tag);
                 lapper_of.insert(ctg_id.clone(), lapper);
             }
         }
 
         lapper_of
     }
+
+    /// Lapper of rgs in one ctg, empty when there are no rgs of the tag
+    pub fn get_idx_rg_ctg(&mut self, ctg_id: &str, tag: Option<&str>) -> Lapper<u32, String> {
+        let key = match tag {
+            Some(tag) => format!("idx:rg:{}:{}", ctg_id, tag),
+            None => format!("idx:rg:{}", ctg_id),
+        };
+        let bytes: Vec<u8> = self.get_bin(&key);
+        if bytes.is_empty() {
+            return Lapper::new(vec![]);
+        }
+        bincode::deserialize(&bytes).unwrap()
+    }
 }
 
 /// INTERFACE: lua scripting and pipeline
@@ -13,6 +13,104 @@ pub fn stddev(data: &[f32]) -> f32 {
     (sq_sum / (len - 1.)).sqrt()
 }
 
+/// The middle value, or the mean of the two middle values
+///
+/// ```
+/// assert_eq!(gams::median(&[3., 1., 2.]), 2.);
+/// assert_eq!(gams::median(&[4., 1., 2., 3.]), 2.5);
+/// ```
+pub fn median(data: &[f32]) -> f32 {
+    let mut sorted = data.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.
+    } else {
+        sorted[mid]
+    }
+}
+
+/// Percentile bootstrap confidence interval of the mean
+///
+/// `n` resamples are drawn with replacement, and the `(1 - level) / 2` and `(1 + level) / 2`
+/// quantiles of their means are returned
+pub fn bootstrap_ci(data: &[f32], n: usize, level: f32, rng: &mut impl rand::Rng) -> (f32, f32) {
+    let mut means: Vec<f32> = (0..n)
+        .map(|_| {
+            let sum: f32 = (0..data.len())
+                .map(|_| data[rng.gen_range(0..data.len())])
+                .sum();
+            sum / data.len() as f32
+        })
+        .collect();
+    means.sort_by(|a, b| a.total_cmp(b));
+
+    let quantile = |q: f32| {
+        let idx = (q * (n - 1) as f32).round() as usize;
+        means[idx]
+    };
+
+    (quantile((1. - level) / 2.), quantile((1. + level) / 2.))
+}
+
+/// Mean and variance of a window sliding along the data, updated in O(1) by Welford's method
+///
+/// ```
+/// let mut rolling = gams::RollingStat::new(&[1., 2., 3.]);
+/// assert_eq!(rolling.mean(), 2.);
+/// assert_eq!(rolling.stddev(), 1.);
+///
+/// // 2, 3, 4
+/// rolling.replace(1., 4.);
+/// assert_eq!(rolling.mean(), 3.);
+/// assert_eq!(rolling.stddev(), 1.);
+/// ```
+pub struct RollingStat {
+    len: f64,
+    mean: f64,
+    // sum of squares of differences from the mean
+    m2: f64,
+}
+
+impl RollingStat {
+    pub fn new(data: &[f32]) -> Self {
+        let mut rolling = Self {
+            len: 0.,
+            mean: 0.,
+            m2: 0.,
+        };
+        for x in data {
+            rolling.len += 1.;
+            let delta = *x as f64 - rolling.mean;
+            rolling.mean += delta / rolling.len;
+            rolling.m2 += delta * (*x as f64 - rolling.mean);
+        }
+        rolling
+    }
+
+    /// Slides the window, `old` leaves and `new` enters
+    pub fn replace(&mut self, old: f32, new: f32) {
+        let (old, new) = (old as f64, new as f64);
+        let prev_mean = self.mean;
+        self.mean += (new - old) / self.len;
+        self.m2 += (new - old) * (new - self.mean + old - prev_mean);
+        // rounding errors
+        if self.m2 < 0. {
+            self.m2 = 0.;
+        }
+    }
+
+    pub fn mean(&self) -> f32 {
+        self.mean as f32
+    }
+
+    /// Sample standard deviation, like `stddev()`
+    pub fn stddev(&self) -> f32 {
+        (self.m2 / (self.len - 1.)).sqrt() as f32
+    }
+}
+
 pub fn thresholding_algo(data: &[f32], lag: usize, threshold: f32, influence: f32) -> Vec<i32> {
     //  the results (peaks, 1 or -1)
     let mut signals: Vec<i32> = vec![0; data.len()];
@@ -20,22 +118,17 @@ pub fn thresholding_algo(data: &[f32], lag: usize, threshold: f32, influence: f3
     // filter out the signals (peaks) from original list (using influence arg)
     let mut filtered_data: Vec<f32> = data.to_owned();
 
-    // the current average of the rolling window
-    let mut avg_filter: Vec<f32> = vec![0.; data.len()];
-
-    // the current standard deviation of the rolling window
-    let mut std_filter: Vec<f32> = vec![0.; data.len()];
-
-    // init avg_filter & std_filter
-    avg_filter[lag - 1] = mean(&data[0..lag]);
-    std_filter[lag - 1] = stddev(&data[0..lag]);
+    // statistics of the rolling window, filtered_data[(i - lag)..i]
+    let mut rolling = RollingStat::new(&data[0..lag]);
+    let mut avg_filter = rolling.mean();
+    let mut std_filter = rolling.stddev();
 
     // loop input starting at end of rolling window
     for i in lag..data.len() {
         // if the distance between the current value and average is enough standard deviations (threshold) away
-        if (data[i] - avg_filter[i - 1]).abs() > threshold * std_filter[i - 1] {
+        if (data[i] - avg_filter).abs() > threshold * std_filter {
             // this is a signal (i.e. peak), determine if it is a positive or negative signal
-            signals[i] = if data[i] > avg_filter[i - 1] { 1 } else { -1 };
+            signals[i] = if data[i] > avg_filter { 1 } else { -1 };
 
             // filter this signal out using influence
             // $filteredY[$i] = $influence * $y->[$i] + (1 - $influence) * $filteredY[$i-1];
@@ -48,8 +141,194 @@ pub fn thresholding_algo(data: &[f32], lag: usize, threshold: f32, influence: f3
         }
 
         // update average & deviation
-        avg_filter[i] = mean(&filtered_data[(i - lag)..i]);
-        std_filter[i] = stddev(&filtered_data[(i - lag)..i]);
+        if i > lag {
+            rolling.replace(filtered_data[i - lag - 1], filtered_data[i - 1]);
+        }
+        avg_filter = rolling.mean();
+        std_filter = rolling.stddev();
+    }
+
+    signals
+}
+
+/// z-scores of each point against the mean and the standard deviation of the previous `lag`
+/// points, points before `lag` are 0
+fn rolling_zscore(data: &[f32], lag: usize) -> Vec<f32> {
+    let mut zs: Vec<f32> = vec![0.; data.len()];
+    if data.len() <= lag {
+        return zs;
+    }
+
+    let mut rolling = RollingStat::new(&data[0..lag]);
+    for i in lag..data.len() {
+        if i > lag {
+            rolling.replace(data[i - lag - 1], data[i - 1]);
+        }
+        let std = rolling.stddev();
+        if std > 0. {
+            zs[i] = (data[i] - rolling.mean()) / std;
+        }
+    }
+
+    zs
+}
+
+/// Peaks by the continuous wavelet transform with Ricker (Mexican hat) wavelets
+///
+/// Scales are `lag / 16`, `lag / 8` and `lag / 4`, so the widest wavelet spans about `lag`
+/// points. Coefficients of each scale are standardized, and a point signals when the
+/// coefficient with the largest absolute z-score exceeds `threshold`
+pub fn cwt_algo(data: &[f32], lag: usize, threshold: f32) -> Vec<i32> {
+    let mut signals: Vec<i32> = vec![0; data.len()];
+    if data.len() < 2 {
+        return signals;
+    }
+
+    let avg = mean(data);
+    let centered: Vec<f32> = data.iter().map(|x| x - avg).collect();
+
+    // the strongest standardized coefficient of each point
+    let mut best: Vec<f32> = vec![0.; data.len()];
+    for divisor in [16, 8, 4] {
+        let scale = (lag / divisor).max(1) as f32;
+
+        // Ricker wavelet, truncated at 4 scales
+        let half = (4. * scale).ceil() as i64;
+        let wavelet: Vec<f32> = (-half..=half)
+            .map(|t| {
+                let x = t as f32 / scale;
+                (1. - x * x) * (-x * x / 2.).exp() / scale.sqrt()
+            })
+            .collect();
+
+        let coefs: Vec<f32> = (0..data.len() as i64)
+            .map(|i| {
+                (-half..=half)
+                    .filter_map(|t| {
+                        let j = i + t;
+                        if j < 0 || j >= data.len() as i64 {
+                            None
+                        } else {
+                            Some(centered[j as usize] * wavelet[(t + half) as usize])
+                        }
+                    })
+                    .sum()
+            })
+            .collect();
+
+        let coef_avg = mean(&coefs);
+        let coef_std = stddev(&coefs);
+        if coef_std == 0. {
+            continue;
+        }
+        for (i, coef) in coefs.iter().enumerate() {
+            let z = (coef - coef_avg) / coef_std;
+            if z.abs() > best[i].abs() {
+                best[i] = z;
+            }
+        }
+    }
+
+    for (i, z) in best.iter().enumerate() {
+        if z.abs() > threshold {
+            signals[i] = if *z > 0. { 1 } else { -1 };
+        }
+    }
+
+    signals
+}
+
+/// Two-sided CUSUM change-point detection
+///
+/// Deviations are z-scores against the previous `lag` points. The positive and negative sums
+/// accumulate deviations beyond a drift of half the `threshold`, and points signal while a
+/// sum exceeds `threshold`. Sums are capped at twice the `threshold`, so signals end soon
+/// after the shift
+pub fn cusum_algo(data: &[f32], lag: usize, threshold: f32) -> Vec<i32> {
+    let mut signals: Vec<i32> = vec![0; data.len()];
+    let drift = threshold / 2.;
+
+    let zs = rolling_zscore(data, lag);
+
+    let mut pos_sum: f32 = 0.;
+    let mut neg_sum: f32 = 0.;
+    for i in lag..data.len() {
+        pos_sum = (pos_sum + zs[i] - drift).clamp(0., 2. * threshold);
+        neg_sum = (neg_sum - zs[i] - drift).clamp(0., 2. * threshold);
+
+        if pos_sum > threshold {
+            signals[i] = 1;
+        } else if neg_sum > threshold {
+            signals[i] = -1;
+        }
+    }
+
+    signals
+}
+
+/// Segmentation by a two-state hidden Markov model
+///
+/// z-scores against the previous `lag` points are emitted by a background state, N(0, 1), or
+/// a peak state, N(0, threshold^2). The most likely path is found by the Viterbi algorithm,
+/// and points of the peak state signal by the signs of their z-scores
+pub fn hmm_algo(data: &[f32], lag: usize, threshold: f32) -> Vec<i32> {
+    let mut signals: Vec<i32> = vec![0; data.len()];
+    if data.len() <= lag {
+        return signals;
+    }
+
+    let zs = rolling_zscore(data, lag);
+
+    // log probabilities, peaks are rare and short
+    let trans = [
+        [(0.99f32).ln(), (0.01f32).ln()],
+        [(0.1f32).ln(), (0.9f32).ln()],
+    ];
+    let sds = [1., threshold.max(1.)];
+    let emit = |state: usize, z: f32| -> f32 {
+        let sd: f32 = sds[state];
+        -(z * z) / (2. * sd * sd) - sd.ln()
+    };
+
+    // Viterbi
+    let n = data.len() - lag;
+    let mut scores: Vec<[f32; 2]> = vec![[0.; 2]; n];
+    let mut backs: Vec<[usize; 2]> = vec![[0; 2]; n];
+    scores[0] = [
+        (0.99f32).ln() + emit(0, zs[lag]),
+        (0.01f32).ln() + emit(1, zs[lag]),
+    ];
+    for k in 1..n {
+        for state in 0..2 {
+            let from_0 = scores[k - 1][0] + trans[0][state];
+            let from_1 = scores[k - 1][1] + trans[1][state];
+            let (score, back) = if from_0 >= from_1 {
+                (from_0, 0)
+            } else {
+                (from_1, 1)
+            };
+            scores[k][state] = score + emit(state, zs[lag + k]);
+            backs[k][state] = back;
+        }
+    }
+
+    let mut state = if scores[n - 1][0] >= scores[n - 1][1] {
+        0
+    } else {
+        1
+    };
+    for k in (0..n).rev() {
+        if state == 1 {
+            let z = zs[lag + k];
+            signals[lag + k] = if z > 0. {
+                1
+            } else if z < 0. {
+                -1
+            } else {
+                0
+            };
+        }
+        state = backs[k][state];
     }
 
     signals
@@ -79,3 +358,43 @@ fn thresholding_sample() {
     ];
     assert_eq!(thresholding_algo(&input, 30, 5., 0.), exp);
 }
+
+#[test]
+fn bootstrap_ci_sample() {
+    use rand::SeedableRng;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+
+    let input: Vec<f32> = (1..=100).map(|e| e as f32).collect();
+    let (lower, upper) = bootstrap_ci(&input, 1000, 0.95, &mut rng);
+    assert!(lower < 50.5 && lower > 40.);
+    assert!(upper > 50.5 && upper < 61.);
+
+    // constant data
+    let (lower, upper) = bootstrap_ci(&[2., 2., 2.], 100, 0.95, &mut rng);
+    assert_eq!((lower, upper), (2., 2.));
+}
+
+#[test]
+fn peak_methods_sample() {
+    let input: Vec<f32> = vec![
+        1.0, 1.0, 1.1, 1.0, 0.9, 1.0, 1.0, 1.1, 1.0, 0.9, //
+        1.0, 1.1, 1.0, 1.0, 0.9, 1.0, 1.0, 1.1, 1.0, 1.0, //
+        1.0, 1.0, 1.1, 0.9, 1.0, 1.1, 1.0, 1.0, 0.9, 1.0, //
+        1.1, 1.0, 1.0, 1.1, 1.0, 0.8, 0.9, 1.0, 1.2, 0.9, //
+        1.0, 1.0, 1.1, 1.2, 1.0, 1.5, 1.0, 3.0, 2.0, 5.0, //
+        3.0, 2.0, 1.0, 1.0, 1.0, 0.9, 1.0, 1.0, 3.0, 2.6, //
+        4.0, 3.0, 3.2, 2.0, 1.0, 1.0, 0.8, 4.0, 4.0, 2.0, //
+        2.5, 1.0, 1.0, 1.0,
+    ];
+
+    for signals in [
+        cwt_algo(&input, 30, 2.),
+        cusum_algo(&input, 30, 5.),
+        hmm_algo(&input, 30, 5.),
+    ] {
+        assert_eq!(signals.len(), input.len());
+        // the flat head is quiet, and the highest point is a crest
+        assert!(signals[..45].iter().all(|e| *e == 0));
+        assert_eq!(signals[49], 1);
+    }
+}
@@ -1,9 +1,49 @@
+use itertools::Itertools;
 use lazy_static::lazy_static;
 use regex::Regex;
 use rust_lapper::Lapper;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::io::BufRead;
 
+/// Replace an aliased chromosome name with the chr_id stored in gams
+pub fn resolve_chr(alias_of: &BTreeMap<String, String>, rg: &mut intspan::Range) {
+    if let Some(chr_id) = alias_of.get(rg.chr()) {
+        rg.chr = chr_id.to_string();
+    }
+}
+
+/// Rename the chromosomes of a runlist set, merging sets sharing the same chr_id
+pub fn resolve_set(
+    set: &BTreeMap<String, intspan::IntSpan>,
+    alias_of: &BTreeMap<String, String>,
+    chrs: &BTreeSet<String>,
+) -> BTreeMap<String, intspan::IntSpan> {
+    let mut resolved: BTreeMap<String, intspan::IntSpan> = BTreeMap::new();
+    let mut unresolved: BTreeSet<String> = BTreeSet::new();
+
+    for (chr, ints) in set {
+        let chr_id = alias_of.get(chr).unwrap_or(chr);
+        if !chrs.contains(chr_id) {
+            unresolved.insert(chr.to_string());
+            continue;
+        }
+
+        resolved.entry(chr_id.to_string()).or_default().merge(ints);
+    }
+    report_unresolved(&unresolved);
+
+    resolved
+}
+
+pub fn report_unresolved(unresolved: &BTreeSet<String>) {
+    if !unresolved.is_empty() {
+        eprintln!(
+            "Unresolved chromosome names: {}",
+            unresolved.iter().join(", ")
+        );
+    }
+}
+
 pub fn find_one_idx(
     lapper_of: &BTreeMap<String, Lapper<u32, String>>,
     rg: &intspan::Range,
@@ -13,7 +53,8 @@ pub fn find_one_idx(
     }
 
     let lapper = lapper_of.get(rg.chr()).unwrap();
-    let res = lapper.find(*rg.start() as u32, *rg.end() as u32).next();
+    // Interval is [start, stop)
+    let res = lapper.find(*rg.start() as u32, *rg.end() as u32 + 1).next();
 
     match res {
         Some(iv) => iv.val.clone(),
@@ -21,6 +62,29 @@ pub fn find_one_idx(
     }
 }
 
+/// All ctgs overlapping with the range, along with the sub-ranges clipped by each ctg
+pub fn find_all_idx(
+    lapper_of: &BTreeMap<String, Lapper<u32, String>>,
+    rg: &intspan::Range,
+) -> Vec<(String, intspan::Range)> {
+    let mut res: Vec<(String, intspan::Range)> = vec![];
+
+    let lapper = match lapper_of.get(rg.chr()) {
+        Some(lapper) => lapper,
+        None => return res,
+    };
+
+    // Interval is [start, stop)
+    for iv in lapper.find(*rg.start() as u32, *rg.end() as u32 + 1) {
+        let start = (*rg.start()).max(iv.start as i32);
+        let end = (*rg.end()).min(iv.stop as i32 - 1);
+        res.push((iv.val.clone(), intspan::Range::from(rg.chr(), start, end)));
+    }
+    res.sort_by_key(|(_, sub)| sub.start);
+
+    res
+}
+
 pub fn count_rg(
     lapper_of: &BTreeMap<String, Lapper<u32, String>>,
     ctg_id: &str,
@@ -31,24 +95,284 @@ pub fn count_rg(
         return 0;
     }
 
+    // Interval is [start, stop)
     let lapper = lapper_of.get(ctg_id).unwrap();
-    lapper.count(*rg.start() as u32, *rg.end() as u32) as i32
+    lapper.count(*rg.start() as u32, *rg.end() as u32 + 1) as i32
+}
+
+/// Values and overlapping lengths of rgs in this range
+///
+/// rgs without values are skipped
+pub fn find_rg_value(
+    lapper_of: &BTreeMap<String, Lapper<u32, String>>,
+    ctg_id: &str,
+    rg: &intspan::Range,
+) -> Vec<(f32, i32)> {
+    let lapper = match lapper_of.get(ctg_id) {
+        Some(lapper) => lapper,
+        None => {
+            eprintln!("{} not found in idx", ctg_id);
+            return vec![];
+        }
+    };
+
+    // Interval is [start, stop)
+    lapper
+        .find(*rg.start() as u32, *rg.end() as u32 + 1)
+        .filter_map(|iv| {
+            let value = iv.val.parse::<f32>().ok()?;
+            let start = (*rg.start()).max(iv.start as i32);
+            let end = (*rg.end()).min(iv.stop as i32 - 1);
+            Some((value, end - start + 1))
+        })
+        .collect()
+}
+
+/// Aggregate values of rgs, `None` when there are no values
+///
+/// * count - number of values
+/// * sum, mean, max
+/// * wmean - mean weighted by overlapping lengths
+///
+/// ```
+/// let values = vec![(1.0, 10), (4.0, 40)];
+/// assert_eq!(gams::agg_value(&values, "count"), Some(2.0));
+/// assert_eq!(gams::agg_value(&values, "sum"), Some(5.0));
+/// assert_eq!(gams::agg_value(&values, "mean"), Some(2.5));
+/// assert_eq!(gams::agg_value(&values, "max"), Some(4.0));
+/// assert_eq!(gams::agg_value(&values, "wmean"), Some(3.4));
+/// assert_eq!(gams::agg_value(&[], "mean"), None);
+/// ```
+pub fn agg_value(values: &[(f32, i32)], agg: &str) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let sum: f32 = values.iter().map(|(v, _)| v).sum();
+    let res = match agg {
+        "count" => values.len() as f32,
+        "sum" => sum,
+        "mean" => sum / values.len() as f32,
+        "max" => values.iter().map(|(v, _)| *v).fold(f32::MIN, f32::max),
+        "wmean" => {
+            let bp: i32 = values.iter().map(|(_, l)| l).sum();
+            values.iter().map(|(v, l)| v * *l as f32).sum::<f32>() / bp as f32
+        }
+        _ => unreachable!(),
+    };
+
+    Some(res)
+}
+
+/// The --format argument shared by subcommands reading ranges from files
+pub fn format_arg() -> clap::Arg {
+    clap::Arg::new("format")
+        .long("format")
+        .num_args(1)
+        .value_parser([
+            clap::builder::PossibleValue::new("auto"),
+            clap::builder::PossibleValue::new("range"),
+            clap::builder::PossibleValue::new("bed"),
+            clap::builder::PossibleValue::new("gff"),
+            clap::builder::PossibleValue::new("vcf"),
+        ])
+        .default_value("auto")
+        .help("Format of input files")
+}
+
+/// Resolve `auto` by the file extension, gzipped files are supported
+///
+/// ```
+/// assert_eq!(gams::detect_format("tests/S288c/spo11_hot.bed", "auto"), "bed");
+/// assert_eq!(gams::detect_format("genes.gff3.gz", "auto"), "gff");
+/// assert_eq!(gams::detect_format("genes.GTF", "auto"), "gff");
+/// assert_eq!(gams::detect_format("snp.vcf.gz", "auto"), "vcf");
+/// assert_eq!(gams::detect_format("spo11_hot.rg", "auto"), "range");
+/// assert_eq!(gams::detect_format("spo11_hot.txt", "bed"), "bed");
+/// ```
+pub fn detect_format(infile: &str, opt_format: &str) -> String {
+    if opt_format != "auto" {
+        return opt_format.to_string();
+    }
+
+    let name = infile.to_ascii_lowercase();
+    let name = name.strip_suffix(".gz").unwrap_or(&name);
+
+    let format = if name.ends_with(".bed") {
+        "bed"
+    } else if name.ends_with(".gff") || name.ends_with(".gff3") || name.ends_with(".gtf") {
+        "gff"
+    } else if name.ends_with(".vcf") {
+        "vcf"
+    } else {
+        "range"
+    };
+
+    format.to_string()
+}
+
+/// Convert a line of range, BED, GFF3/GTF or VCF files to a Range
+///
+/// * range - `I:100-200` in the first field
+/// * bed - 0-based, half-open
+/// * gff - 1-based, closed
+/// * vcf - 1-based, covering the REF allele
+///
+/// Headers, comments and malformed lines return None
+///
+/// ```
+/// let rg = gams::line_to_range("I:100-200\tfoo", "range").unwrap();
+/// assert_eq!(rg.to_string(), "I:100-200");
+/// let rg = gams::line_to_range("I\t99\t200\tfoo\t0\t-", "bed").unwrap();
+/// assert_eq!(rg.to_string(), "I(-):100-200");
+/// let rg = gams::line_to_range("I\tSGD\tgene\t100\t200\t.\t+\t.\tID=foo", "gff").unwrap();
+/// assert_eq!(rg.to_string(), "I(+):100-200");
+/// let rg = gams::line_to_range("I\t100\t.\tACG\tA\t.\tPASS\t.", "vcf").unwrap();
+/// assert_eq!(rg.to_string(), "I:100-102");
+/// assert!(gams::line_to_range("#CHROM\tPOS", "vcf").is_none());
+/// assert!(gams::line_to_range("track name=foo", "bed").is_none());
+/// ```
+pub fn line_to_range(line: &str, format: &str) -> Option<intspan::Range> {
+    if line.starts_with('#') {
+        return None;
+    }
+
+    let parts: Vec<&str> = line.split('\t').collect();
+    let rg = match format {
+        "bed" => {
+            if parts.len() < 3 {
+                return None;
+            }
+            let start = parts[1].parse::<i32>().ok()? + 1;
+            let end = parts[2].parse::<i32>().ok()?.max(start);
+            let strand = parts.get(5).filter(|e| **e == "+" || **e == "-");
+            intspan::Range::from_full("", parts[0], strand.unwrap_or(&""), start, end)
+        }
+        "gff" => {
+            if parts.len() < 9 {
+                return None;
+            }
+            let start = parts[3].parse::<i32>().ok()?;
+            let end = parts[4].parse::<i32>().ok()?;
+            let strand = if parts[6] == "+" || parts[6] == "-" {
+                parts[6]
+            } else {
+                ""
+            };
+            intspan::Range::from_full("", parts[0], strand, start, end)
+        }
+        "vcf" => {
+            if parts.len() < 4 {
+                return None;
+            }
+            let start = parts[1].parse::<i32>().ok()?;
+            let end = start + parts[3].len().max(1) as i32 - 1;
+            intspan::Range::from(parts[0], start, end)
+        }
+        _ => intspan::Range::from_str(parts[0]),
+    };
+
+    if rg.is_valid() {
+        Some(rg)
+    } else {
+        None
+    }
+}
+
+/// All ranges in the file, chromosome names aren't resolved
+///
+/// ```
+/// let rgs = gams::read_ranges("tests/S288c/spo11_hot.bed", "auto");
+/// assert_eq!(rgs.len(), 79);
+/// assert_eq!(rgs[0].to_string(), "I:396-498");
+/// ```
+pub fn read_ranges(infile: &str, opt_format: &str) -> Vec<intspan::Range> {
+    let reader = intspan::reader(infile);
+    let format = detect_format(infile, opt_format);
+
+    reader
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| line_to_range(&line, &format))
+        .collect()
+}
+
+/// Writes a JSON record of `ctg`, `feature`, `rg` or `peak` as a TSV row
+pub fn write_record<W: std::io::Write>(
+    tsv_wtr: &mut csv::Writer<W>,
+    group: &str,
+    json: &str,
+) -> anyhow::Result<()> {
+    match group {
+        "ctg" => tsv_wtr.serialize(serde_json::from_str::<crate::Ctg>(json)?)?,
+        "feature" => tsv_wtr.serialize(serde_json::from_str::<crate::Feature>(json)?)?,
+        "rg" => tsv_wtr.serialize(serde_json::from_str::<crate::Rg>(json)?)?,
+        "peak" => tsv_wtr.serialize(serde_json::from_str::<crate::Peak>(json)?)?,
+        _ => anyhow::bail!("Unknown group {}", group),
+    }
+
+    Ok(())
+}
+
+/// Writes windows as TSV rows under the union of their columns
+///
+/// Per-tag and --anno columns vary between runs of `gams sw`, and peak columns between targets,
+/// missing ones are empty
+pub fn write_sws<W: std::io::Write>(
+    tsv_wtr: &mut csv::Writer<W>,
+    sws: &[crate::Sw],
+) -> anyhow::Result<()> {
+    let mut headers: Vec<String> = vec![];
+    for sw in sws {
+        for header in sw.headers() {
+            if !headers.contains(&header) {
+                headers.push(header);
+            }
+        }
+    }
+    if headers.is_empty() {
+        return Ok(());
+    }
+    tsv_wtr.write_record(&headers)?;
+
+    for sw in sws {
+        let line = sw.to_string();
+        let value_of: HashMap<String, &str> =
+            sw.headers().into_iter().zip(line.split('\t')).collect();
+        tsv_wtr.write_record(
+            headers
+                .iter()
+                .map(|e| value_of.get(e).copied().unwrap_or_default()),
+        )?;
+    }
+
+    Ok(())
 }
 
 /// Read ranges in the file
+/// Chromosome names are resolved via `alias_of`
 pub fn read_range(
     infile: &str,
+    opt_format: &str,
     lapper_of: &BTreeMap<String, Lapper<u32, String>>,
+    alias_of: &BTreeMap<String, String>,
 ) -> BTreeMap<String, Vec<intspan::Range>> {
     let reader = intspan::reader(infile);
+    let format = detect_format(infile, opt_format);
 
     // ctg_id => [Range]
     let mut ranges_of: BTreeMap<String, Vec<intspan::Range>> = BTreeMap::new();
+    let mut unresolved: BTreeSet<String> = BTreeSet::new();
 
     // processing each line
     for line in reader.lines().map_while(Result::ok) {
-        let rg = intspan::Range::from_str(&line);
-        if !rg.is_valid() {
+        let mut rg = match line_to_range(&line, &format) {
+            Some(rg) => rg,
+            None => continue,
+        };
+        resolve_chr(alias_of, &mut rg);
+        if !lapper_of.contains_key(rg.chr()) {
+            unresolved.insert(rg.chr().to_string());
             continue;
         }
 
@@ -57,15 +381,253 @@ pub fn read_range(
             continue;
         }
 
-        ranges_of
-            .entry(ctg_id)
-            .and_modify(|v| v.push(rg))
-            .or_default();
+        ranges_of.entry(ctg_id).or_default().push(rg);
     }
+    report_unresolved(&unresolved);
 
     ranges_of
 }
 
+/// Read ranges along with values in the `idx_value` field, 1-based
+///
+/// Values are `None` when `idx_value` is 0 or the field isn't a number
+pub fn read_range_value(
+    infile: &str,
+    opt_format: &str,
+    idx_value: usize,
+    lapper_of: &BTreeMap<String, Lapper<u32, String>>,
+    alias_of: &BTreeMap<String, String>,
+) -> BTreeMap<String, Vec<(intspan::Range, Option<f32>)>> {
+    let reader = intspan::reader(infile);
+    let format = detect_format(infile, opt_format);
+
+    // ctg_id => [(Range, value)]
+    let mut ranges_of: BTreeMap<String, Vec<(intspan::Range, Option<f32>)>> = BTreeMap::new();
+    let mut unresolved: BTreeSet<String> = BTreeSet::new();
+
+    // processing each line
+    for line in reader.lines().map_while(Result::ok) {
+        let mut rg = match line_to_range(&line, &format) {
+            Some(rg) => rg,
+            None => continue,
+        };
+        resolve_chr(alias_of, &mut rg);
+        if !lapper_of.contains_key(rg.chr()) {
+            unresolved.insert(rg.chr().to_string());
+            continue;
+        }
+
+        let value = if idx_value == 0 {
+            None
+        } else {
+            line.split('\t')
+                .nth(idx_value - 1)
+                .and_then(|e| e.trim().parse::<f32>().ok())
+        };
+
+        let ctg_id = find_one_idx(lapper_of, &rg);
+        if ctg_id.is_empty() {
+            continue;
+        }
+
+        ranges_of.entry(ctg_id).or_default().push((rg, value));
+    }
+    report_unresolved(&unresolved);
+
+    ranges_of
+}
+
+/// Attributes of GFF3, `ID=foo;Name=bar`, or GTF, `gene_id "foo"; gene_name "bar";`
+///
+/// ```
+/// let attrs = gams::parse_gff_attrs("ID=gene:YAL063C;Name=FLO9;biotype=protein_coding");
+/// assert_eq!(attrs.get("ID").unwrap(), "gene:YAL063C");
+/// assert_eq!(attrs.get("biotype").unwrap(), "protein_coding");
+/// let attrs = gams::parse_gff_attrs(r#"gene_id "YAL063C"; gene_name "FLO9";"#);
+/// assert_eq!(attrs.get("gene_name").unwrap(), "FLO9");
+/// ```
+pub fn parse_gff_attrs(field: &str) -> BTreeMap<String, String> {
+    let mut attrs: BTreeMap<String, String> = BTreeMap::new();
+
+    for pair in field.split(';').map(|e| e.trim()).filter(|e| !e.is_empty()) {
+        let (key, val) = match pair.split_once('=') {
+            Some(kv) => kv,
+            None => match pair.split_once(' ') {
+                Some(kv) => kv,
+                None => continue,
+            },
+        };
+        attrs.insert(
+            key.trim().to_string(),
+            val.trim().trim_matches('"').to_string(),
+        );
+    }
+
+    attrs
+}
+
+/// Read gene models in a GFF3/GTF file
+/// Features of `types` are kept, ids are left empty
+pub fn read_gff(
+    infile: &str,
+    types: &BTreeSet<String>,
+    tag: &str,
+    lapper_of: &BTreeMap<String, Lapper<u32, String>>,
+    alias_of: &BTreeMap<String, String>,
+) -> BTreeMap<String, Vec<crate::Feature>> {
+    let reader = intspan::reader(infile);
+
+    // ctg_id => [Feature]
+    let mut features_of: BTreeMap<String, Vec<crate::Feature>> = BTreeMap::new();
+    let mut unresolved: BTreeSet<String> = BTreeSet::new();
+
+    for line in reader.lines().map_while(Result::ok) {
+        let mut rg = match line_to_range(&line, "gff") {
+            Some(rg) => rg,
+            None => continue,
+        };
+
+        let parts: Vec<&str> = line.split('\t').collect();
+        let feature_type = parts[2];
+        if !types.contains(feature_type) {
+            continue;
+        }
+
+        resolve_chr(alias_of, &mut rg);
+        if !lapper_of.contains_key(rg.chr()) {
+            unresolved.insert(rg.chr().to_string());
+            continue;
+        }
+
+        let ctg_id = find_one_idx(lapper_of, &rg);
+        if ctg_id.is_empty() {
+            continue;
+        }
+
+        // GTF has no ID and Parent
+        let attrs = parse_gff_attrs(parts[8]);
+        let get_attr = |keys: &[&str]| keys.iter().find_map(|k| attrs.get(*k).cloned());
+        let (gtf_id, gtf_parent) = match feature_type {
+            "gene" => (get_attr(&["gene_id"]), None),
+            "mRNA" | "transcript" => (get_attr(&["transcript_id"]), get_attr(&["gene_id"])),
+            _ => (None, get_attr(&["transcript_id"])),
+        };
+
+        let feature = crate::Feature {
+            id: "".to_string(),
+            range: rg.to_string(),
+            length: rg.end() - rg.start() + 1,
+            tag: tag.to_string(),
+            strand: Some(rg.strand().to_string()).filter(|e| !e.is_empty()),
+            feature_type: Some(feature_type.to_string()),
+            name: get_attr(&["Name", "gene_name"]),
+            biotype: get_attr(&["biotype", "gene_biotype", "transcript_biotype", "gene_type"]),
+            gff_id: get_attr(&["ID"]).or(gtf_id),
+            parent: get_attr(&["Parent"]).or(gtf_parent),
+        };
+
+        features_of.entry(ctg_id).or_default().push(feature);
+    }
+    report_unresolved(&unresolved);
+
+    features_of
+}
+
+/// Keep the first one of features with identical types and ranges, and also tags with `by_tag`
+///
+/// ```
+/// let feature = |tag: &str| gams::Feature {
+///     range: "I:100-200".to_string(),
+///     tag: tag.to_string(),
+///     ..Default::default()
+/// };
+/// let features = vec![feature("hot"), feature("cold"), feature("hot")];
+///
+/// assert_eq!(gams::dedup_features(&features, false).len(), 1);
+/// assert_eq!(gams::dedup_features(&features, true).len(), 2);
+/// ```
+pub fn dedup_features(features: &[crate::Feature], by_tag: bool) -> Vec<crate::Feature> {
+    let mut seen: BTreeSet<(Option<String>, Option<String>, String)> = BTreeSet::new();
+
+    features
+        .iter()
+        .filter(|f| {
+            let tag = by_tag.then(|| f.tag.clone());
+            seen.insert((tag, f.feature_type.clone(), f.range.clone()))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Merge overlapping features of the same tag and type
+///
+/// The merged feature keeps other fields of the first one, and its strand is kept only if all
+/// features are on the same strand
+///
+/// ```
+/// let feature = |range: &str| gams::Feature {
+///     range: range.to_string(),
+///     tag: "hot".to_string(),
+///     ..Default::default()
+/// };
+/// let features = vec![feature("I:100-200"), feature("I:300-400"), feature("I:150-250")];
+///
+/// let merged = gams::merge_features(&features);
+/// assert_eq!(merged.len(), 2);
+/// assert_eq!(merged[0].range, "I:100-250");
+/// assert_eq!(merged[0].length, 151);
+/// assert_eq!(merged[1].range, "I:300-400");
+/// ```
+pub fn merge_features(features: &[crate::Feature]) -> Vec<crate::Feature> {
+    // (tag, type) => [(Range, Feature)]
+    type Group<'a> = Vec<(intspan::Range, &'a crate::Feature)>;
+    let mut group_of: BTreeMap<(String, Option<String>), Group> = BTreeMap::new();
+    for f in features {
+        let rg = intspan::Range::from_str(&f.range);
+        group_of
+            .entry((f.tag.clone(), f.feature_type.clone()))
+            .or_default()
+            .push((rg, f));
+    }
+
+    let mut merged: Vec<(intspan::Range, crate::Feature)> = vec![];
+    for group in group_of.values_mut() {
+        group.sort_by_key(|(rg, _)| rg.start);
+
+        let mut cur: Option<(intspan::Range, crate::Feature)> = None;
+        for (rg, f) in group.iter() {
+            match cur.as_mut() {
+                Some((cur_rg, cur_f)) if rg.start <= cur_rg.end => {
+                    cur_rg.end = cur_rg.end.max(rg.end);
+                    if cur_rg.strand() != rg.strand() {
+                        *cur_rg.strand_mut() = "".to_string();
+                        cur_f.strand = None;
+                    }
+                }
+                _ => {
+                    if let Some(done) = cur.take() {
+                        merged.push(done);
+                    }
+                    cur = Some((rg.clone(), (*f).clone()));
+                }
+            }
+        }
+        if let Some(done) = cur.take() {
+            merged.push(done);
+        }
+    }
+    merged.sort_by_key(|(rg, _)| rg.start);
+
+    merged
+        .into_iter()
+        .map(|(rg, f)| crate::Feature {
+            range: rg.to_string(),
+            length: rg.end - rg.start + 1,
+            ..f
+        })
+        .collect()
+}
+
 pub fn ctg_range_tuple(
     ranges_of: &BTreeMap<String, Vec<intspan::Range>>,
 ) -> Vec<(String, intspan::Range)> {
@@ -80,26 +642,48 @@ pub fn ctg_range_tuple(
 
 /// Read peaks in the file
 /// gc_content in this file aren't correct
+///
+/// Signals are in the 3rd field of range files, the name field of BED
+/// and the score field of GFF
 pub fn read_peak(
     infile: &str,
+    opt_format: &str,
     lapper_of: &BTreeMap<String, Lapper<u32, String>>,
+    alias_of: &BTreeMap<String, String>,
 ) -> BTreeMap<String, Vec<(intspan::Range, String)>> {
     let reader = intspan::reader(infile);
+    let format = detect_format(infile, opt_format);
+
+    // index of the signal field
+    let idx_signal = match format.as_str() {
+        "bed" => 3,
+        "gff" => 5,
+        _ => 2,
+    };
 
     // ctg_id => [Range]
     let mut peaks_of: BTreeMap<String, Vec<(intspan::Range, String)>> = BTreeMap::new();
+    let mut unresolved: BTreeSet<String> = BTreeSet::new();
 
     // processing each line
     for line in reader.lines().map_while(Result::ok) {
         let parts: Vec<&str> = line.split('\t').collect();
 
-        let mut rg = intspan::Range::from_str(parts[0]);
-        if !rg.is_valid() {
+        let mut rg = match line_to_range(&line, &format) {
+            Some(rg) => rg,
+            None => continue,
+        };
+        *rg.strand_mut() = "".to_string();
+        resolve_chr(alias_of, &mut rg);
+        if !lapper_of.contains_key(rg.chr()) {
+            unresolved.insert(rg.chr().to_string());
             continue;
         }
-        *rg.strand_mut() = "".to_string();
 
-        let signal = parts[2];
+        let signal = match parts.get(idx_signal) {
+            Some(signal) => *signal,
+            None => continue,
+        };
 
         let ctg_id = crate::find_one_idx(lapper_of, &rg);
         if ctg_id.is_empty() {
@@ -108,9 +692,10 @@ pub fn read_peak(
 
         peaks_of
             .entry(ctg_id)
-            .and_modify(|v| v.push((rg, signal.to_string())))
-            .or_default();
+            .or_default()
+            .push((rg, signal.to_string()));
     }
+    report_unresolved(&unresolved);
 
     peaks_of
 }
@@ -128,6 +713,19 @@ pub fn extract_ctg_id(input: &str) -> Option<&str> {
         .and_then(|cap| cap.name("ctg").map(|ctg| ctg.as_str()))
 }
 
+/// The serial number at the end of an id, 0 if there isn't one
+///
+/// ```
+/// assert_eq!(gams::extract_serial("feature:ctg:I:1:12"), 12);
+/// assert_eq!(gams::extract_serial("ctg:I"), 0);
+/// ```
+pub fn extract_serial(id: &str) -> i32 {
+    id.rsplit(':')
+        .next()
+        .and_then(|e| e.parse::<i32>().ok())
+        .unwrap_or(0)
+}
+
 /// ```
 /// assert_eq!(gams::round(4.364, 2), 4.36);
 /// assert_eq!(gams::round(4.368, 2), 4.37);
@@ -137,28 +735,167 @@ pub fn round(x: f32, decimals: u32) -> f32 {
     (x * y).round() / y
 }
 
+/// Proportion of `rg` covered by a runlist set, parts of the set within each ctg are cached
+///
+/// ```
+/// use std::collections::{BTreeMap, HashMap};
+/// let set = BTreeMap::from([("I".to_string(), intspan::IntSpan::from("1-100,201-300"))]);
+/// let parent = intspan::IntSpan::from_pair(1, 1000);
+/// let mut cache = HashMap::new();
+///
+/// let rg = intspan::Range::from_str("I:51-250");
+/// assert_eq!(gams::cache_prop(&set, "ctg:I:1", &parent, &rg, &mut cache), 0.5);
+/// let rg = intspan::Range::from_str("II:51-250");
+/// assert_eq!(gams::cache_prop(&set, "ctg:II:1", &parent, &rg, &mut cache), 0.0);
+/// ```
+pub fn cache_prop(
+    set: &BTreeMap<String, intspan::IntSpan>,
+    ctg_id: &str,
+    parent: &intspan::IntSpan,
+    rg: &intspan::Range,
+    cache: &mut HashMap<String, intspan::IntSpan>,
+) -> f32 {
+    let chr_set = match set.get(rg.chr()) {
+        Some(chr_set) => chr_set,
+        None => return 0.0,
+    };
+
+    if !cache.contains_key(ctg_id) {
+        cache.insert(ctg_id.to_string(), chr_set.intersect(parent));
+    }
+
+    let intspan = rg.intspan();
+    let intxn = cache.get(ctg_id).unwrap().intersect(&intspan);
+    intxn.cardinality() as f32 / intspan.cardinality() as f32
+}
+
 /// GC-content within a ctg
-pub fn cache_gc_content(
+///
+/// ```
+/// let parent = intspan::IntSpan::from_pair(101, 110);
+/// let counts = gams::BaseCount::new("ATGCNNgcat");
+///
+/// let rg = intspan::Range::from_str("I:103-108");
+/// assert_eq!(gams::range_gc_content(&rg, &parent, &counts), 0.6667);
+/// ```
+pub fn range_gc_content(
     rg: &intspan::Range,
     parent: &intspan::IntSpan,
-    seq: &str,
-    cache: &mut HashMap<String, f32>,
+    counts: &crate::BaseCount,
 ) -> f32 {
-    let field = rg.to_string();
+    // converted to ctg index
+    let from = parent.index(*rg.start()) as usize;
+    let to = parent.index(*rg.end()) as usize;
 
-    if !cache.contains_key(&field) {
-        // converted to ctg index
-        let from = parent.index(*rg.start()) as usize;
-        let to = parent.index(*rg.end()) as usize;
+    round(counts.gc_content(from, to), 4)
+}
 
-        // from <= x < to, zero-based
-        let sub_seq = seq.get((from - 1)..(to)).unwrap().bytes();
+/// Counts of A, C, G and T, case-insensitive
+fn count_acgt(seq: &[u8]) -> [usize; 4] {
+    let mut counts = [0; 4];
+    for base in seq {
+        match base.to_ascii_uppercase() {
+            b'A' => counts[0] += 1,
+            b'C' => counts[1] += 1,
+            b'G' => counts[2] += 1,
+            b'T' => counts[3] += 1,
+            _ => {}
+        }
+    }
+    counts
+}
 
-        let gc_content = bio::seq_analysis::gc::gc_content(sub_seq);
-        cache.insert(field.clone(), gc_content);
-    };
+/// Column name of values of `gams wave --stat`
+///
+/// ```
+/// assert_eq!(gams::stat_column("gc"), "gc_content");
+/// assert_eq!(gams::stat_column("cpg"), "cpg_oe");
+/// ```
+pub fn stat_column(stat: &str) -> &'static str {
+    match stat {
+        "gc-skew" => "gc_skew",
+        "at-skew" => "at_skew",
+        "cpg" => "cpg_oe",
+        "entropy" => "entropy",
+        "gibbs" => "gibbs",
+        _ => "gc_content",
+    }
+}
+
+/// GC skew, (G - C) / (G + C)
+///
+/// ```
+/// assert_eq!(gams::gc_skew(b"GGGC"), 0.5);
+/// assert_eq!(gams::gc_skew(b"cccgAT"), -0.5);
+/// assert_eq!(gams::gc_skew(b"ATAT"), 0.0);
+/// ```
+pub fn gc_skew(seq: &[u8]) -> f32 {
+    let [_, c, g, _] = count_acgt(seq);
+    if c + g == 0 {
+        0.
+    } else {
+        (g as f32 - c as f32) / (g + c) as f32
+    }
+}
+
+/// AT skew, (A - T) / (A + T)
+///
+/// ```
+/// assert_eq!(gams::at_skew(b"AAAT"), 0.5);
+/// assert_eq!(gams::at_skew(b"GCGC"), 0.0);
+/// ```
+pub fn at_skew(seq: &[u8]) -> f32 {
+    let [a, _, _, t] = count_acgt(seq);
+    if a + t == 0 {
+        0.
+    } else {
+        (a as f32 - t as f32) / (a + t) as f32
+    }
+}
+
+/// Observed/expected ratio of CpG dinucleotides, CpG * length / (C * G)
+///
+/// ```
+/// assert_eq!(gams::cpg_oe(b"ACGT"), 4.0);
+/// assert_eq!(gams::cpg_oe(b"GCAT"), 0.0);
+/// assert_eq!(gams::cpg_oe(b"AAAA"), 0.0);
+/// ```
+pub fn cpg_oe(seq: &[u8]) -> f32 {
+    let [_, c, g, _] = count_acgt(seq);
+    if c == 0 || g == 0 {
+        return 0.;
+    }
 
-    round(*cache.get(&field).unwrap(), 4)
+    let cpg = seq
+        .windows(2)
+        .filter(|w| w[0].eq_ignore_ascii_case(&b'C') && w[1].eq_ignore_ascii_case(&b'G'))
+        .count();
+    (cpg * seq.len()) as f32 / (c * g) as f32
+}
+
+/// Shannon entropy of bases, in bits
+///
+/// ```
+/// assert_eq!(gams::shannon_entropy(b"ACGT"), 2.0);
+/// assert_eq!(gams::shannon_entropy(b"AATT"), 1.0);
+/// assert_eq!(gams::shannon_entropy(b"AAAA"), 0.0);
+/// ```
+pub fn shannon_entropy(seq: &[u8]) -> f32 {
+    let counts = count_acgt(seq);
+    let total: usize = counts.iter().sum();
+    if total == 0 {
+        return 0.;
+    }
+
+    counts
+        .iter()
+        .filter(|e| **e > 0)
+        .map(|e| {
+            let p = *e as f32 / total as f32;
+            -p * p.log2()
+        })
+        .sum::<f32>()
+        .abs()
 }
 
 pub fn gc_stat(gcs: &[f32]) -> (f32, f32, f32) {
@@ -186,11 +923,10 @@ pub fn gc_stat(gcs: &[f32]) -> (f32, f32, f32) {
     (round(mean, 4), round(stddev, 4), round(cv, 4))
 }
 
-pub fn cache_gc_stat(
+pub fn range_gc_stat(
     rg: &intspan::Range,
     parent: &intspan::IntSpan,
-    seq: &str,
-    cache: &mut HashMap<String, f32>,
+    counts: &crate::BaseCount,
     size: i32,
     step: i32,
 ) -> (f32, f32, f32) {
@@ -200,11 +936,10 @@ pub fn cache_gc_stat(
     let mut gcs = Vec::new();
 
     for w in windows {
-        let gc_content = cache_gc_content(
+        let gc_content = range_gc_content(
             &intspan::Range::from(rg.chr(), w.min(), w.max()),
             parent,
-            seq,
-            cache,
+            counts,
         );
         gcs.push(gc_content);
     }
@@ -212,6 +947,69 @@ pub fn cache_gc_stat(
     gc_stat(&gcs)
 }
 
+/// Free energy of the duplex within a ctg, `None` for sequences with Ns
+pub fn cache_gibbs(
+    rg: &intspan::Range,
+    parent: &intspan::IntSpan,
+    seq: &str,
+    dg: &crate::DeltaG,
+    cache: &mut HashMap<String, Option<f32>>,
+) -> Option<f32> {
+    let field = rg.to_string();
+
+    if !cache.contains_key(&field) {
+        // converted to ctg index
+        let from = parent.index(*rg.start()) as usize;
+        let to = parent.index(*rg.end()) as usize;
+
+        // from <= x < to, zero-based
+        let sub_seq = seq.get((from - 1)..(to)).unwrap();
+
+        let gibbs = dg.polymer(sub_seq);
+        cache.insert(field.clone(), gibbs);
+    };
+
+    cache.get(&field).unwrap().map(|v| round(v, 4))
+}
+
+/// Mean and stddev of free energies of sliding windows, windows with Ns are skipped
+pub fn cache_gibbs_stat(
+    rg: &intspan::Range,
+    parent: &intspan::IntSpan,
+    seq: &str,
+    dg: &crate::DeltaG,
+    cache: &mut HashMap<String, Option<f32>>,
+    size: i32,
+    step: i32,
+) -> (Option<f32>, Option<f32>) {
+    let intspan = rg.intspan();
+    let windows = crate::sliding(&intspan, size, step);
+
+    let mut gibbses = Vec::new();
+
+    for w in windows {
+        let gibbs = cache_gibbs(
+            &intspan::Range::from(rg.chr(), w.min(), w.max()),
+            parent,
+            seq,
+            dg,
+            cache,
+        );
+        if let Some(gibbs) = gibbs {
+            gibbses.push(gibbs);
+        }
+    }
+
+    if gibbses.is_empty() {
+        return (None, None);
+    }
+
+    let mean = crate::mean(&gibbses);
+    let stddev = crate::stddev(&gibbses);
+
+    (Some(round(mean, 4)), Some(round(stddev, 4)))
+}
+
 // Adopt from https://rust-lang-nursery.github.io/rust-cookbook/concurrency/threads.html#create-a-parallel-pipeline
 pub fn proc_ctg_p(
     ctgs: &Vec<crate::Ctg>,
@@ -6,23 +6,65 @@ pub fn center_sw(
     end: i32,
     size: i32,
     max: i32,
+    log: bool,
 ) -> Vec<(IntSpan, String, i32)> {
     let mut windows = vec![];
 
     let w0 = center_resize(parent, &IntSpan::from_pair(start, end), size);
     windows.push((w0.clone(), "M".to_string(), 0));
 
+    windows.extend(flank_sw(parent, &w0, size, max, log));
+
+    windows
+}
+
+/// The feature itself is the `M` window at full length, and `L`/`R` windows start at its edges
+pub fn intact_sw(
+    parent: &IntSpan,
+    start: i32,
+    end: i32,
+    size: i32,
+    max: i32,
+    log: bool,
+) -> Vec<(IntSpan, String, i32)> {
+    let mut windows = vec![];
+
+    let w0 = parent.intersect(&IntSpan::from_pair(start, end));
+    if w0.is_empty() {
+        return windows;
+    }
+    windows.push((w0.clone(), "M".to_string(), 0));
+
+    windows.extend(flank_sw(parent, &w0, size, max, log));
+
+    windows
+}
+
+/// `L` and `R` windows next to `body`, distance is from 1 to max
+///
+/// With `log`, widths of windows are doubled with each step away from the body, `size`, `2 *
+/// size`, `4 * size` and so on, i.e. log-spaced distance bins
+pub fn flank_sw(
+    parent: &IntSpan,
+    body: &IntSpan,
+    size: i32,
+    max: i32,
+    log: bool,
+) -> Vec<(IntSpan, String, i32)> {
+    let mut windows = vec![];
+
     for sw_type in ["L", "R"] {
         // sw_start and sw_end are both index of parent
         let mut sw_start;
         let mut sw_end;
 
+        let mut width = size;
         if sw_type == "R" {
-            sw_start = parent.index(w0.max()) + 1;
-            sw_end = sw_start + size - 1;
+            sw_start = parent.index(body.max()) + 1;
+            sw_end = sw_start + width - 1;
         } else {
-            sw_end = parent.index(w0.min()) - 1;
-            sw_start = sw_end - size + 1;
+            sw_end = parent.index(body.min()) - 1;
+            sw_start = sw_end - width + 1;
         }
 
         // distance is from 1 to max
@@ -36,18 +78,21 @@ pub fn center_sw(
 
             let sw_intspan = parent.slice(sw_start, sw_end);
 
-            if sw_intspan.size() < size {
+            if sw_intspan.size() < width {
                 break;
             }
 
             windows.push((sw_intspan.clone(), sw_type.to_string(), sw_distance));
 
+            if log {
+                width *= 2;
+            }
             if sw_type == "R" {
                 sw_start = sw_end + 1;
-                sw_end = sw_start + size - 1;
+                sw_end = sw_start + width - 1;
             } else {
                 sw_end = sw_start - 1;
-                sw_start = sw_end - size + 1;
+                sw_start = sw_end - width + 1;
             }
         }
     }
@@ -55,6 +100,70 @@ pub fn center_sw(
     windows
 }
 
+/// The body of a feature is scaled into `bins` windows of type `B`, with `L`/`R` windows of
+/// `size` outside the body
+pub fn body_sw(
+    parent: &IntSpan,
+    start: i32,
+    end: i32,
+    size: i32,
+    max: i32,
+    log: bool,
+    bins: i32,
+) -> Vec<(IntSpan, String, i32)> {
+    let mut windows = vec![];
+
+    let body = parent.intersect(&IntSpan::from_pair(start, end));
+    if body.is_empty() {
+        return windows;
+    }
+
+    let body_start = parent.index(body.min());
+    let length = body.size();
+    for i in 1..=bins {
+        let bin_start = body_start + length * (i - 1) / bins;
+        let bin_end = body_start + length * i / bins - 1;
+
+        // body shorter than bins
+        if bin_end < bin_start {
+            continue;
+        }
+
+        windows.push((parent.slice(bin_start, bin_end), "B".to_string(), i));
+    }
+
+    windows.extend(flank_sw(parent, &body, size, max, log));
+
+    windows
+}
+
+/// Relabel windows in the direction of transcription
+///
+/// On the `+` strand, `L` becomes `U` (upstream) and `R` becomes `D` (downstream); the `-`
+/// strand is the opposite, and `B` windows out of `bins` are renumbered from the 5' end.
+/// Unstranded windows are kept as they are
+pub fn orient_sw(
+    windows: Vec<(IntSpan, String, i32)>,
+    strand: &str,
+    bins: i32,
+) -> Vec<(IntSpan, String, i32)> {
+    if strand != "+" && strand != "-" {
+        return windows;
+    }
+
+    windows
+        .into_iter()
+        .map(
+            |(ints, sw_type, distance)| match (sw_type.as_str(), strand) {
+                ("L", "+") | ("R", "-") => (ints, "U".to_string(), distance),
+                ("R", "+") | ("L", "-") => (ints, "D".to_string(), distance),
+                ("B", "-") => (ints, sw_type, bins + 1 - distance),
+                _ => (ints, sw_type, distance),
+            },
+        )
+        .collect()
+}
+
 #[test]
 fn test_center_sw() {
     // parent, start, end, exp
@@ -66,7 +175,7 @@ fn test_center_sw() {
     ];
 
     for (parent, start, end, exp) in tests {
-        let windows = center_sw(&IntSpan::from(parent), start, end, 100, 1);
+        let windows = center_sw(&IntSpan::from(parent), start, end, 100, 1, false);
 
         assert_eq!(windows[0].0.to_string(), exp.0);
         assert_eq!(windows[0].1, exp.1);
@@ -75,6 +184,146 @@ fn test_center_sw() {
     }
 }
 
+#[test]
+fn test_intact_sw() {
+    // parent, start, end, exp
+    let tests = vec![
+        ("1-9999", 500, 500, ("500", "M", 0, 3)),
+        ("1-9999", 500, 800, ("500-800", "M", 0, 3)),
+        ("1-9999", 51, 2000, ("51-2000", "M", 0, 2)),
+        ("10001-19999", 10101, 10300, ("10101-10300", "M", 0, 3)),
+        ("1-9999", 20001, 21000, ("-", "", 0, 0)),
+    ];
+
+    for (parent, start, end, exp) in tests {
+        let windows = intact_sw(&IntSpan::from(parent), start, end, 100, 1, false);
+
+        assert_eq!(windows.len(), exp.3);
+        if let Some(w) = windows.first() {
+            assert_eq!(w.0.to_string(), exp.0);
+            assert_eq!(w.1, exp.1);
+            assert_eq!(w.2, exp.2);
+        }
+    }
+
+    // flanks start at the real edges
+    let windows = intact_sw(&IntSpan::from("1-9999"), 500, 800, 100, 1, false);
+    assert_eq!(windows[1].0.to_string(), "400-499");
+    assert_eq!(windows[2].0.to_string(), "801-900");
+}
+
+#[test]
+fn test_flank_sw() {
+    let parent = IntSpan::from("1-9999");
+    let body = IntSpan::from("1001-1100");
+
+    let windows = flank_sw(&parent, &body, 100, 3, false);
+    assert_eq!(windows.len(), 6);
+    assert_eq!(windows[2].0.to_string(), "701-800");
+    assert_eq!(windows[5].0.to_string(), "1301-1400");
+
+    // log-spaced
+    let windows = flank_sw(&parent, &body, 100, 4, true);
+    assert_eq!(windows.len(), 7);
+    assert_eq!(windows[1].0.to_string(), "701-900");
+    assert_eq!(windows[2].0.to_string(), "301-700");
+    assert_eq!(windows[3].1, "R");
+    assert_eq!(windows[6].0.to_string(), "1801-2600");
+}
+
+#[test]
+fn test_body_sw() {
+    // parent, start, end, bins, exp
+    let tests = vec![
+        ("1-9999", 1001, 2000, 4, ("1001-1250", "B", 1, 4 + 2)),
+        ("1-9999", 1001, 1002, 4, ("1001", "B", 2, 2 + 2)),
+        ("1-9999", 51, 2000, 2, ("51-1025", "B", 1, 2 + 1)),
+        ("1-9999", 20001, 21000, 2, ("-", "", 0, 0)),
+    ];
+
+    for (parent, start, end, bins, exp) in tests {
+        let windows = body_sw(&IntSpan::from(parent), start, end, 100, 1, false, bins);
+
+        assert_eq!(windows.len(), exp.3);
+        if let Some(w) = windows.first() {
+            assert_eq!(w.0.to_string(), exp.0);
+            assert_eq!(w.1, exp.1);
+            assert_eq!(w.2, exp.2);
+        }
+    }
+}
+
+#[test]
+fn test_orient_sw() {
+    let parent = IntSpan::from("1-9999");
+
+    // strand, first non-M window, last window
+    let tests = vec![
+        ("+", ("L", "U", 1), ("R", "D", 2)),
+        ("-", ("L", "D", 1), ("R", "U", 2)),
+        ("", ("L", "L", 1), ("R", "R", 2)),
+    ];
+
+    for (strand, exp_first, exp_last) in tests {
+        let windows = orient_sw(center_sw(&parent, 500, 800, 100, 2, false), strand, 0);
+
+        assert_eq!(windows[0].1, "M");
+        assert_eq!(windows[1].1, exp_first.1);
+        assert_eq!(windows[1].2, exp_first.2);
+        assert_eq!(windows.last().unwrap().1, exp_last.1);
+        assert_eq!(windows.last().unwrap().2, exp_last.2);
+    }
+
+    // B windows are numbered from the 5' end
+    let windows = orient_sw(body_sw(&parent, 1001, 2000, 100, 1, false, 4), "-", 4);
+    assert_eq!(windows[0].0.to_string(), "1001-1250");
+    assert_eq!(windows[0].2, 4);
+
+    // a body shorter than bins, only B2 and B4 are emitted
+    let windows = orient_sw(body_sw(&parent, 1001, 1002, 100, 0, false, 4), "-", 4);
+    assert_eq!(windows.len(), 2);
+    assert_eq!(windows[0].0.to_string(), "1001");
+    assert_eq!(windows[0].2, 3);
+    assert_eq!(windows[1].0.to_string(), "1002");
+    assert_eq!(windows[1].2, 1);
+}
+
+/// Find a cut point around `target` within `lower..=upper`
+///
+/// Runs of `boundary` overlapping the window are candidates, and the midpoint nearest to
+/// `target` is returned
+pub fn nearest_cut(boundary: &IntSpan, target: i32, lower: i32, upper: i32) -> Option<i32> {
+    if lower > upper {
+        return None;
+    }
+
+    let candidates = boundary.intersect(&IntSpan::from_pair(lower, upper));
+
+    candidates
+        .spans()
+        .iter()
+        .map(|(start, end)| (start + end) / 2)
+        .min_by_key(|mid| (mid - target).abs())
+}
+
+#[test]
+fn test_nearest_cut() {
+    // boundary, target, lower, upper, exp
+    let tests = vec![
+        ("100-200,900-1000", 500, 300, 700, None),
+        ("100-200,900-1000", 500, 1, 1000, Some(150)),
+        ("100-200,800-1000", 500, 1, 850, Some(825)),
+        ("-", 500, 1, 1000, None),
+        ("100-200", 500, 700, 300, None),
+    ];
+
+    for (boundary, target, lower, upper, exp) in tests {
+        let cut = nearest_cut(&IntSpan::from(boundary), target, lower, upper);
+
+        assert_eq!(cut, exp);
+    }
+}
+
 pub fn sliding(intspan: &IntSpan, size: i32, step: i32) -> Vec<IntSpan> {
     let mut windows = vec![];
 
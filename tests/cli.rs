@@ -173,6 +173,36 @@ fn command_gen() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn command_gen_split() -> anyhow::Result<()> {
+    Command::cargo_bin("gams")?.arg("env").unwrap();
+    Command::cargo_bin("gams")?
+        .arg("status")
+        .arg("drop")
+        .unwrap();
+
+    // gen
+    Command::cargo_bin("gams")?
+        .arg("gen")
+        .arg("tests/S288c/genome.fa.gz")
+        .arg("--piece")
+        .arg("100000")
+        .arg("--split")
+        .arg("runlist")
+        .arg("--runlist")
+        .arg("tests/S288c/intergenic.json")
+        .unwrap();
+
+    // ctgs end within intergenic regions
+    let mut conn = gams::Conn::new();
+    let ctg = conn.get_ctg("ctg:I:1");
+    assert_eq!(ctg.range, "I:1-92585");
+    let ctg = conn.get_ctg("ctg:I:2");
+    assert_eq!(ctg.range, "I:92586-230218");
+
+    Ok(())
+}
+
 fn env_drop_gen() -> anyhow::Result<()> {
     Command::cargo_bin("gams")?.arg("env").unwrap();
     Command::cargo_bin("gams")?
@@ -246,8 +276,165 @@ fn command_rg() -> anyhow::Result<()> {
         .unwrap();
     let stderr = String::from_utf8(output.stderr).unwrap();
 
-    assert_eq!(stderr.lines().count(), 5);
-    assert!(stderr.contains("There are 69 rgs in this file"));
+    assert_eq!(stderr.lines().count(), 7);
+    assert!(stderr.contains("There are 71 rgs in this file"));
+    assert!(stderr.contains("Unresolved chromosome names: II"));
+
+    Ok(())
+}
+
+#[test]
+fn command_rg_value() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    Command::cargo_bin("gams")?
+        .arg("rg")
+        .arg("tests/S288c/I.rate.tsv")
+        .arg("--value")
+        .arg("2")
+        .unwrap();
+
+    // aggregations
+    let tests = vec![
+        ("count", "I:1001-1500\t4\n"),
+        ("sum", "I:1001-1500\t12\n"),
+        ("max", "I:1001-1500\t6\n"),
+        ("wmean", "I:1001-1500\t2.5714\n"),
+    ];
+    for (agg, exp) in tests {
+        let mut cmd = Command::cargo_bin("gams")?;
+        let output = cmd
+            .arg("locate")
+            .arg("--count")
+            .arg("--agg")
+            .arg(agg)
+            .arg("I:1001-1500")
+            .arg("I:3001-4000")
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+
+        assert!(stdout.contains(exp), "{}", agg);
+    }
+
+    // no values
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("locate")
+        .arg("--count")
+        .arg("--agg")
+        .arg("mean")
+        .arg("I:3001-4000")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout, "I:3001-4000\t\n");
+
+    // sw
+    Command::cargo_bin("gams")?
+        .arg("feature")
+        .arg("tests/S288c/spo11_hot.rg")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("sw")
+        .arg("--style")
+        .arg("center")
+        .arg("--action")
+        .arg("count")
+        .arg("--agg")
+        .arg("wmean")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout
+        .lines()
+        .next()
+        .unwrap()
+        .ends_with("\trg_count\trg_density\trg_value"));
+    assert!(stdout.contains("\tI:1396-1495\tfeature\tL\t10\t100\t\t\t\t\t\t\t\t2\t20\t3.9643\n"));
+
+    Ok(())
+}
+
+#[test]
+fn command_rg_tag() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    Command::cargo_bin("gams")?
+        .arg("rg")
+        .arg("tests/S288c/spo11_hot.rg")
+        .arg("--tag")
+        .arg("spo11")
+        .unwrap();
+    Command::cargo_bin("gams")?
+        .arg("rg")
+        .arg("tests/S288c/I.rate.tsv")
+        .arg("--tag")
+        .arg("rate")
+        .arg("--value")
+        .arg("2")
+        .unwrap();
+
+    // all tags
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("locate")
+        .arg("--count")
+        .arg("I:1-230218")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout, "I:1-230218\t79\n");
+
+    // one column per tag
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("locate")
+        .arg("--count")
+        .arg("--tag")
+        .arg("spo11")
+        .arg("--tag")
+        .arg("rate")
+        .arg("I:1-230218")
+        .arg("I:1001-1500")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout, "I:1-230218\t71\t8\nI:1001-1500\t0\t4\n");
+
+    // sw
+    Command::cargo_bin("gams")?
+        .arg("feature")
+        .arg("tests/S288c/spo11_hot.rg")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("sw")
+        .arg("--style")
+        .arg("center")
+        .arg("--action")
+        .arg("count")
+        .arg("--rg-tag")
+        .arg("spo11")
+        .arg("--rg-tag")
+        .arg("rate")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout
+        .lines()
+        .next()
+        .unwrap()
+        .ends_with("\trg_count\trg_density\trg_value\tspo11_count\trate_count"));
+    assert!(stdout.contains("\tI:2396-2495\tfeature\tM\t0\t100\t\t\t\t\t\t\t\t1\t10\t\t1\t0\n"));
 
     Ok(())
 }
@@ -297,185 +484,1328 @@ fn command_feature() -> anyhow::Result<()> {
         .unwrap();
     let stderr = String::from_utf8(output.stderr).unwrap();
 
-    assert_eq!(stderr.lines().count(), 2);
-    assert!(stderr.contains("There are 69 features in this file"));
+    assert_eq!(stderr.lines().count(), 3);
+    assert!(stderr.contains("There are 71 features in this file"));
 
     Ok(())
 }
 
 #[test]
-fn command_sw() -> anyhow::Result<()> {
+fn command_feature_bed() -> anyhow::Result<()> {
     env_drop_gen()?;
 
     // feature
-    Command::cargo_bin("gams")?
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
         .arg("feature")
-        .arg("tests/S288c/spo11_hot.rg")
+        .arg("tests/S288c/spo11_hot.bed")
         .arg("--tag")
         .arg("spo11")
+        .output()
         .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
 
-    // sw
+    assert_eq!(stderr.lines().count(), 3);
+    assert!(stderr.contains("There are 71 features in this file"));
+
+    // BED is 0-based
     let mut cmd = Command::cargo_bin("gams")?;
-    let output = cmd.arg("sw").output().unwrap();
+    let output = cmd.arg("tsv").arg("-s").arg("feature:*").output().unwrap();
     let stdout = String::from_utf8(output.stdout).unwrap();
-    assert!(stdout.lines().count() > 2000);
-    assert!(stdout.contains("sw:feature:ctg:I:2:32:1"));
 
-    let stderr = String::from_utf8(output.stderr).unwrap();
-    assert_eq!(stderr.lines().count(), 7);
-    assert!(stderr.contains("Process ctg:I:2"));
+    assert!(stdout.contains("I:2409-2482\t74\tspo11"));
 
     Ok(())
 }
 
 #[test]
-fn command_wave() -> anyhow::Result<()> {
+fn command_feature_gff() -> anyhow::Result<()> {
     env_drop_gen()?;
 
-    // sliding
+    // feature
     let mut cmd = Command::cargo_bin("gams")?;
     let output = cmd
-        .arg("wave")
-        .arg("--ctg")
-        .arg("ctg:I:*")
-        .arg("--size")
-        .arg("100")
-        .arg("--step")
-        .arg("10")
-        .arg("--lag")
-        .arg("100")
-        .arg("--threshold")
-        .arg("3.0")
-        .arg("--influence")
-        .arg("1.0")
+        .arg("feature")
+        .arg("tests/S288c/I.gff3")
+        .arg("--tag")
+        .arg("gene")
         .output()
         .unwrap();
-    let stdout = String::from_utf8(output.stdout).unwrap();
-    assert_eq!(stdout.lines().count(), 116);
-    assert!(stdout.contains("I:7551-7650\t"));
-    assert!(stdout.contains("I(+):11551-11740\t"));
-
     let stderr = String::from_utf8(output.stderr).unwrap();
-    assert_eq!(stderr.lines().count(), 3);
-    assert!(stderr.contains("Process ctg:I:2"));
 
-    Ok(())
-}
+    assert!(stderr.contains("There are 28 features in this file"));
 
-#[test]
-fn command_peak() -> anyhow::Result<()> {
-    env_drop_gen()?;
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd.arg("tsv").arg("-s").arg("feature:*").output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
 
-    // peak
+    assert!(stdout.contains("I(-):24000-27968\t3969\tgene\t-\tgene\tFLO9\tprotein_coding"));
+    assert!(stdout.contains("\tgene:YAL063C\n"));
+
+    // only exons
+    Command::cargo_bin("gams")?
+        .arg("clear")
+        .arg("feature")
+        .unwrap();
     let mut cmd = Command::cargo_bin("gams")?;
     let output = cmd
-        .arg("peak")
-        .arg("tests/S288c/I.peaks.tsv")
+        .arg("feature")
+        .arg("tests/S288c/I.gff3")
+        .arg("--type")
+        .arg("exon")
         .output()
         .unwrap();
     let stderr = String::from_utf8(output.stderr).unwrap();
 
-    assert!(stderr.contains("Process ctg:I:1"));
+    assert!(stderr.contains("There are 7 features in this file"));
 
     Ok(())
 }
 
 #[test]
-fn command_locate() -> anyhow::Result<()> {
-    // gams env
-    // gams status drop
-    // gams gen tests/S288c/genome.fa.gz --piece 100000
-    // gams locate "I:1000-1100" "II:1000-1100" "Mito:1000-1100"
-    // gams locate -f tests/S288c/spo11_hot.rg
-
+fn command_sw_filter() -> anyhow::Result<()> {
     env_drop_gen()?;
 
-    // locate
-    let mut cmd = Command::cargo_bin("gams")?;
-    let output = cmd
-        .arg("locate")
-        .arg("I:1000-1100")
-        .arg("II:1000-1100")
-        .arg("Mito:1000-1100")
-        .output()
+    Command::cargo_bin("gams")?
+        .arg("feature")
+        .arg("tests/S288c/I.gff3")
+        .arg("--tag")
+        .arg("gene")
         .unwrap();
-    let stdout = String::from_utf8(output.stdout).unwrap();
-
-    assert_eq!(stdout.lines().count(), 2);
-    assert!(stdout.contains("ctg:I:1"));
-    assert!(!stdout.contains("II:1000-1100"));
 
-    // locate -f
+    // sw
     let mut cmd = Command::cargo_bin("gams")?;
     let output = cmd
-        .arg("locate")
-        .arg("-f")
-        .arg("tests/S288c/spo11_hot.rg")
+        .arg("sw")
+        .arg("--type")
+        .arg("gene")
+        .arg("--biotype")
+        .arg("protein_coding")
         .output()
         .unwrap();
     let stdout = String::from_utf8(output.stdout).unwrap();
 
-    assert_eq!(stdout.lines().count(), 71);
-    assert!(stdout.contains("ctg:I:1"));
-    assert!(!stdout.contains("ctg:Mito:1"));
+    // one line for each window ending with :1
+    assert_eq!(
+        stdout
+            .lines()
+            .filter(|e| e.starts_with("sw:") && e.split('\t').next().unwrap().ends_with(":1"))
+            .count(),
+        7
+    );
 
     Ok(())
 }
 
 #[test]
-fn command_locate_count() -> anyhow::Result<()> {
+fn command_edit() -> anyhow::Result<()> {
     env_drop_gen()?;
 
-    // range
+    // twice
+    for _ in 0..2 {
+        Command::cargo_bin("gams")?
+            .arg("feature")
+            .arg("tests/S288c/spo11_hot.rg")
+            .arg("--tag")
+            .arg("spo11")
+            .unwrap();
+    }
     Command::cargo_bin("gams")?
-        .arg("rg")
-        .arg("tests/S288c/SK1.snp.rg")
+        .arg("feature")
+        .arg("tests/S288c/I.gff3")
+        .arg("--tag")
+        .arg("gene")
+        .unwrap();
+    Command::cargo_bin("gams")?
+        .arg("sw")
+        .arg("--max")
+        .arg("1")
+        .arg("--db")
         .unwrap();
 
-    // locate
+    // dedup
     let mut cmd = Command::cargo_bin("gams")?;
     let output = cmd
-        .arg("locate")
-        .arg("--count")
-        .arg("I:1000-2000")
-        .arg("II:1001-2000")
-        .arg("Mito:1000-2000")
+        .arg("edit")
+        .arg("dedup")
+        .arg("--tag")
+        .arg("spo11")
         .output()
         .unwrap();
-    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
 
-    assert_eq!(stdout.lines().count(), 2);
-    assert!(stdout.contains("I:1000-2000\t12"));
-    assert!(!stdout.contains("II:1001-2000"), "chr not in database");
-    assert!(stdout.contains("Mito:1000-2000\t0"), "No overlaps");
+    assert!(stderr.contains("Remove 71 features"));
+    assert!(stderr.contains("There are 99 features in the database"));
 
-    Ok(())
-}
+    // ids and counters are kept, windows of removed features are deleted
+    let mut conn = gams::Conn::new();
+    assert_eq!(conn.get_sn("cnt:feature:ctg:I:1"), 96);
+    assert!(conn
+        .get_str("feature:ctg:I:1:5")
+        .contains("\"range\":\"I:28206-28871\""));
+    assert_eq!(conn.get_scan_count("feature:ctg:I:1:37"), 0);
+    assert_eq!(conn.get_scan_count("sw:feature:ctg:I:1:37:*"), 0);
+    assert_eq!(conn.get_scan_count("sw:feature:ctg:I:1:5:*"), 3);
+
+    // delete by tag and range
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("edit")
+        .arg("delete")
+        .arg("--tag")
+        .arg("spo11")
+        .arg("--range")
+        .arg("I:1-50000")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
 
-#[test]
-fn command_anno() -> anyhow::Result<()> {
-    env_drop_gen()?;
+    assert!(stderr.contains("Remove 15 features"));
 
-    // anno
+    // delete by id
     let mut cmd = Command::cargo_bin("gams")?;
     let output = cmd
-        .arg("anno")
-        .arg("tests/S288c/intergenic.json")
-        .arg("tests/S288c/ctg.range.tsv")
-        .arg("-H")
+        .arg("edit")
+        .arg("delete")
+        .arg("--id")
+        .arg("feature:ctg:I:2:1")
         .output()
         .unwrap();
-    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
 
-    assert_eq!(stdout.lines().count(), 4);
-    assert_eq!(
-        stdout.lines().next().unwrap().split('\t').count(),
-        8,
-        "field count"
-    );
-    assert!(stdout.contains("85779\t0.0000"));
-    assert!(stdout.contains("130218\t0.1072"));
+    assert!(stderr.contains("There are 83 features in the database"));
+
+    // delete by chromosome
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("edit")
+        .arg("delete")
+        .arg("--range")
+        .arg("I")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(stderr.contains("There are 0 features in the database"));
+
+    // nothing selected
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd.arg("edit").arg("delete").output().unwrap();
+    assert!(!output.status.success());
+
+    // identical ranges of different tags
+    for tag in ["hot", "cold"] {
+        Command::cargo_bin("gams")?
+            .arg("feature")
+            .arg("tests/S288c/spo11_hot.rg")
+            .arg("--tag")
+            .arg(tag)
+            .unwrap();
+    }
+
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("edit")
+        .arg("dedup")
+        .arg("--by-tag")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Remove 0 features"));
+
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd.arg("edit").arg("dedup").output().unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Remove 71 features"));
+
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd.arg("tsv").arg("-s").arg("feature:*").output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().filter(|e| e.contains("\thot\t")).count(), 71);
+    assert!(!stdout.contains("\tcold\t"));
+
+    Ok(())
+}
+
+#[test]
+fn command_query() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    Command::cargo_bin("gams")?
+        .arg("feature")
+        .arg("tests/S288c/spo11_hot.rg")
+        .arg("--tag")
+        .arg("spo11")
+        .unwrap();
+    Command::cargo_bin("gams")?
+        .arg("feature")
+        .arg("tests/S288c/I.gff3")
+        .arg("--tag")
+        .arg("gene")
+        .unwrap();
+
+    // tsv
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd.arg("query").arg("I:10000-30000").output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(stdout.lines().count(), 7);
+    assert!(stdout.starts_with("id\trange\tlength\ttag"));
+    assert!(stdout.contains("feature:ctg:I:1:5\tI:28206-28871\t666\tspo11"));
+    assert!(stderr.contains("There are 6 features overlapping the given ranges"));
+
+    // tags and json
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("query")
+        .arg("--tag")
+        .arg("gene")
+        .arg("--json")
+        .arg("I:10000-30000")
+        .arg("I:71786-71800")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 8);
+    assert!(stdout.contains("\"name\":\"CDC19\""));
+
+    // range files
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("query")
+        .arg("--file")
+        .arg("tests/S288c/spo11_hot.bed")
+        .arg("--tag")
+        .arg("spo11")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(stderr.contains("There are 71 features overlapping the given ranges"));
+
+    // rgs stored without a tag have the default one
+    let mut conn = gams::Conn::new();
+    conn.insert_str(
+        "rg:ctg:I:1:1",
+        r#"{"id":"rg:ctg:I:1:1","range":"I:500-600"}"#,
+    );
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("query")
+        .arg("--group")
+        .arg("rg")
+        .arg("--tag")
+        .arg("rg")
+        .arg("I:550-560")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("rg:ctg:I:1:1\tI:500-600\trg\t"));
+
+    Ok(())
+}
+
+#[test]
+fn command_sw() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    // feature
+    Command::cargo_bin("gams")?
+        .arg("feature")
+        .arg("tests/S288c/spo11_hot.rg")
+        .arg("--tag")
+        .arg("spo11")
+        .unwrap();
+
+    // sw
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd.arg("sw").output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.lines().count() > 2000);
+    assert!(stdout.contains("sw:feature:ctg:I:2:32:1"));
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(stderr.lines().count(), 7);
+    assert!(stderr.contains("Process ctg:I:2"));
+
+    Ok(())
+}
+
+#[test]
+fn command_sw_count() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    Command::cargo_bin("gams")?
+        .arg("feature")
+        .arg("tests/S288c/spo11_hot.rg")
+        .unwrap();
+    Command::cargo_bin("gams")?
+        .arg("rg")
+        .arg("tests/S288c/spo11_hot.rg")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("sw")
+        .arg("--style")
+        .arg("center")
+        .arg("--action")
+        .arg("count")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains(
+        "sw:feature:ctg:I:1:2:1\tI:2396-2495\tfeature\tM\t0\t100\t\t\t\t\t\t\t\t1\t10\t\n"
+    ));
+
+    // each feature is an rg
+    let counts: Vec<i32> = stdout
+        .lines()
+        .filter(|e| e.split('\t').nth(3) == Some("M"))
+        .map(|e| e.split('\t').nth(13).unwrap().parse::<i32>().unwrap())
+        .collect();
+    assert_eq!(counts.len(), 71);
+    assert!(counts.iter().all(|e| *e >= 1));
+
+    Ok(())
+}
+
+#[test]
+fn command_sw_style() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    Command::cargo_bin("gams")?
+        .arg("feature")
+        .arg("tests/S288c/I.gff3")
+        .arg("--type")
+        .arg("gene")
+        .unwrap();
+
+    // YAL063C at full length
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd.arg("sw").arg("--max").arg("1").output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("sw:feature:ctg:I:1:1:1\tI:24000-27968\tfeature\tM\t0\t100\t0.4465\t"));
+    assert!(stdout.contains("sw:feature:ctg:I:1:1:2\tI:23900-23999\tfeature\tD\t1\t100\t"));
+    assert!(stdout.contains("sw:feature:ctg:I:1:1:3\tI:27969-28068\tfeature\tU\t1\t100\t"));
+
+    // collapsed to the center
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("sw")
+        .arg("--style")
+        .arg("center")
+        .arg("--max")
+        .arg("1")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("sw:feature:ctg:I:1:1:1\tI:25934-26033\tfeature\tM\t0\t100\t0.46\t"));
+    assert!(stdout.contains("sw:feature:ctg:I:1:1:2\tI:25834-25933\tfeature\tD\t1\t100\t"));
+
+    Ok(())
+}
+
+#[test]
+fn command_sw_peak() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    Command::cargo_bin("gams")?
+        .arg("peak")
+        .arg("tests/S288c/I.peaks.tsv")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("sw")
+        .arg("peak")
+        .arg("--max")
+        .arg("1")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout
+        .lines()
+        .next()
+        .unwrap()
+        .ends_with("\tpeak_signal\tpeak_gc\tleft_wave_length\tright_wave_length"));
+    assert!(stdout.contains("sw:peak:ctg:I:1:2:1\tI:3091-3210\tpeak\tM\t0\t100\t0.25\t"));
+    assert!(stdout.contains("sw:peak:ctg:I:1:2:2\tI:2991-3090\tpeak\tL\t1\t100\t"));
+    assert!(stdout.contains("\t-1\t0.25\t1312\t1232\n"));
+    assert!(!stdout.contains("sw:feature:"));
+
+    Ok(())
+}
+
+#[test]
+fn command_sw_db() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    Command::cargo_bin("gams")?
+        .arg("feature")
+        .arg("tests/S288c/spo11_hot.rg")
+        .arg("--tag")
+        .arg("spo11")
+        .unwrap();
+
+    Command::cargo_bin("gams")?
+        .arg("sw")
+        .arg("--max")
+        .arg("1")
+        .arg("--db")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd.arg("tsv").arg("-s").arg("sw:*").output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 213);
+    assert!(stdout.starts_with("id\trange\ttag\ttype\tdistance\t"));
+    assert!(stdout.contains("sw:feature:ctg:I:1:2:1\tI:2409-2482\tspo11\tM\t0\t100\t0.4189\t"));
+
+    // clear
+    Command::cargo_bin("gams")?.arg("clear").arg("sw").unwrap();
+
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd.arg("tsv").arg("-s").arg("sw:*").output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn command_sw_anno() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    Command::cargo_bin("gams")?
+        .arg("feature")
+        .arg("tests/S288c/spo11_hot.rg")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("sw")
+        .arg("--anno")
+        .arg("tests/S288c/intergenic.json")
+        .arg("--anno")
+        .arg("ig=tests/S288c/intergenic.json")
+        .arg("--db")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout
+        .lines()
+        .next()
+        .unwrap()
+        .ends_with("\trg_value\tintergenicProp\tigProp"));
+    assert!(stdout.contains(
+        "\tI:27906-28005\tfeature\tL\t3\t100\t0.33\t0.35\t0.0552\t0.1578\t\t\t\t\t\t\t0.3700\t0.3700\n"
+    ));
+    assert!(stdout.contains(
+        "\tI:28206-28871\tfeature\tM\t0\t100\t0.3664\t0.362\t0.0239\t0.066\t\t\t\t\t\t\t1.0000\t1.0000\n"
+    ));
+
+    // stored windows keep --anno columns
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd.arg("tsv").arg("-s").arg("sw:*").output().unwrap();
+    let tsv = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(tsv.lines().next(), stdout.lines().next());
+    assert!(tsv.contains(
+        "\tI:27906-28005\tfeature\tL\t3\t100\t0.33\t0.35\t0.0552\t0.1578\t\t\t\t\t\t\t0.3700\t0.3700\n"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn command_sw_size() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    Command::cargo_bin("gams")?
+        .arg("feature")
+        .arg("tests/S288c/spo11_hot.rg")
+        .unwrap();
+
+    // multiple sizes
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("sw")
+        .arg("--style")
+        .arg("center")
+        .arg("--size")
+        .arg("50,100")
+        .arg("--max")
+        .arg("2")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("sw:feature:ctg:I:1:2:1\tI:2421-2470\tfeature\tM\t0\t50\t0.44\t"));
+    assert!(stdout.contains("sw:feature:ctg:I:1:2:6\tI:2396-2495\tfeature\tM\t0\t100\t0.4\t"));
+    assert!(stdout.contains("sw:feature:ctg:I:1:2:10\tI:2596-2695\tfeature\tR\t2\t100\t"));
+
+    // log-spaced
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("sw")
+        .arg("--max")
+        .arg("3")
+        .arg("--log")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("sw:feature:ctg:I:1:2:3\tI:2109-2308\tfeature\tL\t2\t100\t"));
+    assert!(stdout.contains("sw:feature:ctg:I:1:2:7\tI:2783-3182\tfeature\tR\t3\t100\t"));
+
+    Ok(())
+}
+
+#[test]
+fn command_sw_anchor() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    Command::cargo_bin("gams")?
+        .arg("feature")
+        .arg("tests/S288c/I.gff3")
+        .arg("--type")
+        .arg("gene")
+        .unwrap();
+
+    // TSS of YAL063C, on the minus strand
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("sw")
+        .arg("--anchor")
+        .arg("5p")
+        .arg("--max")
+        .arg("2")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("sw:feature:ctg:I:1:1:1\tI:27919-28017\tfeature\tM\t0"));
+    assert!(stdout.contains("sw:feature:ctg:I:1:1:2\tI:27819-27918\tfeature\tD\t1"));
+    assert!(stdout.contains("sw:feature:ctg:I:1:1:4\tI:28018-28117\tfeature\tU\t1"));
+
+    // body-scaled
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("sw")
+        .arg("--anchor")
+        .arg("body")
+        .arg("--bins")
+        .arg("3")
+        .arg("--max")
+        .arg("1")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(
+        stdout.lines().filter(|e| e.contains("\tB\t")).count(),
+        7 * 3
+    );
+    assert!(stdout.contains("sw:feature:ctg:I:1:1:1\tI:24000-25322\tfeature\tB\t3"));
+
+    Ok(())
+}
+
+#[test]
+fn command_sw_gibbs() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    Command::cargo_bin("gams")?
+        .arg("feature")
+        .arg("tests/S288c/spo11_hot.rg")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("sw")
+        .arg("--style")
+        .arg("center")
+        .arg("--action")
+        .arg("gibbs")
+        .arg("--max")
+        .arg("1")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout
+        .lines()
+        .next()
+        .unwrap()
+        .contains("\tgibbs\tgibbs_mean\tgibbs_stddev\t"));
+    assert!(stdout
+        .contains("\tI:2396-2495\tfeature\tM\t0\t100\t\t\t\t\t-126.8639\t-126.3235\t3.1007\t"));
+
+    // temperature and salt
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("sw")
+        .arg("--style")
+        .arg("center")
+        .arg("--action")
+        .arg("gibbs")
+        .arg("--max")
+        .arg("1")
+        .arg("--temp")
+        .arg("30")
+        .arg("--salt")
+        .arg("0.1")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("\tI:2396-2495\tfeature\tM\t0\t100\t\t\t\t\t-116.6031\t"));
+
+    Ok(())
+}
+
+#[test]
+fn command_swstat() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    Command::cargo_bin("gams")?
+        .arg("feature")
+        .arg("tests/S288c/spo11_hot.rg")
+        .arg("--tag")
+        .arg("spo11")
+        .unwrap();
+
+    let tempdir = TempDir::new().unwrap();
+    let sw_file = tempdir.path().join("sw.tsv");
+    Command::cargo_bin("gams")?
+        .arg("sw")
+        .arg("--max")
+        .arg("3")
+        .arg("--anno")
+        .arg("tests/S288c/intergenic.json")
+        .arg("--db")
+        .arg("-o")
+        .arg(&sw_file)
+        .unwrap();
+
+    // from the output of sw
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("swstat")
+        .arg(&sw_file)
+        .arg("--metric")
+        .arg("gc_content")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 8);
+    assert!(stdout.starts_with(
+        "tag\ttype\tdistance\tbase_size\tmetric\tcount\tmean\tstddev\tmedian\tci_lower\tci_upper\n"
+    ));
+    assert!(stdout
+        .contains("\nspo11\tL\t1\t100\tgc_content\t71\t0.3966\t0.0795\t0.38\t0.3785\t0.4155\n"));
+
+    // from stored windows, grouped by tags
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("swstat")
+        .arg("--db")
+        .arg("--boot")
+        .arg("0")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 1 + 7 * 5);
+    assert!(stdout.contains("spo11\tM\t0\t100\tgc_content\t71\t0.3657\t0.0674\t0.3686\t\t\n"));
+    assert!(stdout.contains("spo11\tM\t0\t100\tintergenicProp\t71\t0.234\t0.4207\t0\t\t\n"));
+
+    Ok(())
+}
+
+#[test]
+fn command_wave() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    // sliding
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("wave")
+        .arg("--ctg")
+        .arg("ctg:I:*")
+        .arg("--size")
+        .arg("100")
+        .arg("--step")
+        .arg("10")
+        .arg("--lag")
+        .arg("100")
+        .arg("--threshold")
+        .arg("3.0")
+        .arg("--influence")
+        .arg("1.0")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 116);
+    assert!(stdout.contains("I:7551-7650\t"));
+    assert!(stdout.contains("I(+):11551-11740\t"));
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(stderr.lines().count(), 3);
+    assert!(stderr.contains("Process ctg:I:2"));
+
+    Ok(())
+}
+
+#[test]
+fn command_wave_method() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    for (method, count, peak) in [
+        ("cwt", 49, "I(+):1871-2040\t0.51\t1\n"),
+        ("cusum", 228, "I(+):1841-2110\t0.52\t1\n"),
+        ("hmm", 166, "I(+):1751-2020\t0.4\t1\n"),
+    ] {
+        let mut cmd = Command::cargo_bin("gams")?;
+        let output = cmd
+            .arg("wave")
+            .arg("--ctg")
+            .arg("ctg:I:*")
+            .arg("--method")
+            .arg(method)
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+
+        assert_eq!(stdout.lines().count(), count);
+        assert!(stdout.contains(peak));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn command_wave_stat() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    for (stat, count, peak) in [
+        (
+            "gc-skew",
+            96,
+            "#range\tgc_skew\tsignal\nI:4931-5030\t-0.3889\t-1\n",
+        ),
+        (
+            "at-skew",
+            102,
+            "#range\tat_skew\tsignal\nI:1431-1530\t0.3208\t1\n",
+        ),
+        (
+            "cpg",
+            109,
+            "#range\tcpg_oe\tsignal\nI(+):1091-1240\t2.1368\t1\n",
+        ),
+        (
+            "entropy",
+            168,
+            "#range\tentropy\tsignal\nI(+):1661-1780\t1.6713\t-1\n",
+        ),
+        (
+            "gibbs",
+            120,
+            "#range\tgibbs\tsignal\nI:1371-1470\t-141.1572\t-1\n",
+        ),
+    ] {
+        let mut cmd = Command::cargo_bin("gams")?;
+        let output = cmd
+            .arg("wave")
+            .arg("--ctg")
+            .arg("ctg:I:*")
+            .arg("--stat")
+            .arg(stat)
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+
+        assert_eq!(stdout.lines().count(), count);
+        assert!(stdout.starts_with(peak));
+    }
+
+    // stored series are exported under the column of --stat
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("wave")
+        .arg("--ctg")
+        .arg("ctg:I:1")
+        .arg("--stat")
+        .arg("gibbs")
+        .arg("--signal")
+        .arg("--db")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd.arg("tsv").arg("-s").arg("wave:*").output().unwrap();
+    let tsv = String::from_utf8(output.stdout).unwrap();
+
+    assert!(tsv.starts_with("range\tgibbs\tsignal\n"));
+    assert_eq!(
+        tsv.lines().skip(1).collect::<Vec<_>>(),
+        stdout.lines().skip(1).collect::<Vec<_>>()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn command_parallel_order() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    Command::cargo_bin("gams")?
+        .arg("feature")
+        .arg("tests/S288c/spo11_hot.rg")
+        .unwrap();
+
+    // parallel runs are identical to serial runs
+    for subcommand in ["wave", "sw"] {
+        let mut outputs = vec![];
+        for parallel in ["1", "4"] {
+            let mut cmd = Command::cargo_bin("gams")?;
+            let output = cmd
+                .arg(subcommand)
+                .arg("--parallel")
+                .arg(parallel)
+                .output()
+                .unwrap();
+            outputs.push(String::from_utf8(output.stdout).unwrap());
+        }
+
+        assert!(outputs[0].lines().count() > 100);
+        assert_eq!(outputs[0], outputs[1]);
+
+        // in the order of ctg ids, I before Mito
+        let is_mito: Vec<bool> = outputs[0]
+            .lines()
+            .skip(1)
+            .map(|e| e.contains("Mito"))
+            .collect();
+        assert!(is_mito.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn command_wave_summary() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("wave")
+        .arg("--ctg")
+        .arg("ctg:I:*")
+        .arg("--summary")
+        .arg("10")
+        .arg("--db")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    // 10 + 14 bins
+    assert_eq!(stdout.lines().count(), 25);
+    assert!(stdout.starts_with("#range\tcrests\ttroughs\twave_length\tamplitude\n"));
+    assert!(stdout.contains("I:1-10000\t2\t4\t1543.6666\t0.1217\n"));
+
+    // stored GC series
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd.arg("tsv").arg("-s").arg("wave:*").output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 9991 + 13012 + 1);
+    assert!(stdout.contains("I:1-100\t0.55\t0\n"));
+
+    Command::cargo_bin("gams")?
+        .arg("clear")
+        .arg("wave")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd.arg("tsv").arg("-s").arg("wave:*").output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn command_peak() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    // peak
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("peak")
+        .arg("tests/S288c/I.peaks.tsv")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(stderr.contains("Process ctg:I:1"));
+
+    Ok(())
+}
+
+#[test]
+fn command_peak_cross() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    // I:99951-100050 crosses ctg:I:1 and ctg:I:2
+    Command::cargo_bin("gams")?
+        .arg("peak")
+        .arg("tests/S288c/I.cross.peaks.tsv")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd.arg("tsv").arg("-s").arg("peak:*").output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    // GC-content of the part within the ctg
+    assert!(stdout.contains("\tI:99951-100050\t100\t0.32\t-1\t"));
+
+    Ok(())
+}
+
+#[test]
+fn command_locate() -> anyhow::Result<()> {
+    // gams env
+    // gams status drop
+    // gams gen tests/S288c/genome.fa.gz --piece 100000
+    // gams locate "I:1000-1100" "II:1000-1100" "Mito:1000-1100"
+    // gams locate -f tests/S288c/spo11_hot.rg
+
+    env_drop_gen()?;
+
+    // locate
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("locate")
+        .arg("I:1000-1100")
+        .arg("II:1000-1100")
+        .arg("Mito:1000-1100")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 2);
+    assert!(stdout.contains("ctg:I:1"));
+    assert!(!stdout.contains("II:1000-1100"));
+
+    // locate -f
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("locate")
+        .arg("-f")
+        .arg("tests/S288c/spo11_hot.rg")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 71);
+    assert!(stdout.contains("ctg:I:1"));
+    assert!(!stdout.contains("ctg:Mito:1"));
+
+    Ok(())
+}
+
+#[test]
+fn command_locate_all() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    // locate --all
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("locate")
+        .arg("--all")
+        .arg("I:99990-100010")
+        .arg("I:1000-1010")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 3);
+    assert!(stdout.contains("I:99990-100010\tctg:I:1\tI:99990-100000"));
+    assert!(stdout.contains("I:99990-100010\tctg:I:2\tI:100001-100010"));
+    assert!(stdout.contains("I:1000-1010\tctg:I:1\tI:1000-1010"));
+
+    // locate --seq across ctgs
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("locate")
+        .arg("--seq")
+        .arg("I:99990-100010")
+        .arg("Mito:85770-90000")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 4);
+    assert!(stdout.contains("CAGCTACTAAAGGTATTAttt"));
+    assert!(stdout.contains(">Mito:85770-90000\nAATATCCATA\n"));
+
+    Ok(())
+}
+
+#[test]
+fn command_locate_seq() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    // strand
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("locate")
+        .arg("--seq")
+        .arg("I(+):1000-1010")
+        .arg("I(-):1000-1010")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 4);
+    assert!(stdout.contains(">I(+):1000-1010\nATACAATTATA\n"));
+    assert!(stdout.contains(">I(-):1000-1010\nTATAATTGTAT\n"));
+
+    // flanking and wrapping
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("locate")
+        .arg("--seq")
+        .arg("--up")
+        .arg("3")
+        .arg("--down")
+        .arg("2")
+        .arg("--line")
+        .arg("10")
+        .arg("I(+):1000-1010")
+        .arg("I(-):1000-1010")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 6);
+    assert!(stdout.contains(">I(+):997-1012\nATGATACAAT\nTATATC\n"));
+    assert!(stdout.contains(">I(-):998-1013\nAGATATAATT\nGTATCA\n"));
+
+    Ok(())
+}
+
+#[test]
+fn command_alias() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    // alias
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("alias")
+        .arg("tests/S288c/chr.alias.tsv")
+        .arg("tests/S288c/chr.ucsc.sizes")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(stderr.lines().count(), 2);
+    assert!(stderr.contains("Unresolved chromosome names: chrII"));
+    assert!(stderr.contains("There are 4 aliases in the database"));
+
+    // locate
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("locate")
+        .arg("chrI:1000-1100")
+        .arg("chrII:1000-1100")
+        .arg("chrmt:1000-1100")
+        .arg("NC_001224:1000-1100")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 3);
+    assert!(stdout.contains("chrI:1000-1100\tctg:I:1"));
+    assert!(stdout.contains("chrmt:1000-1100\tctg:Mito:1"));
+    assert!(stdout.contains("NC_001224:1000-1100\tctg:Mito:1"));
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Unresolved chromosome names: chrII"));
+
+    Ok(())
+}
+
+#[test]
+fn command_locate_count() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    // range
+    Command::cargo_bin("gams")?
+        .arg("rg")
+        .arg("tests/S288c/SK1.snp.rg")
+        .unwrap();
+
+    // locate
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("locate")
+        .arg("--count")
+        .arg("I:1000-2000")
+        .arg("II:1001-2000")
+        .arg("Mito:1000-2000")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 2);
+    assert!(stdout.contains("I:1000-2000\t12"));
+    assert!(!stdout.contains("II:1001-2000"), "chr not in database");
+    assert!(stdout.contains("Mito:1000-2000\t0"), "No overlaps");
+
+    // rgs at both ends are counted, the SNP I:1025
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("locate")
+        .arg("--count")
+        .arg("I:1000-1025")
+        .arg("I:1025-1263")
+        .arg("I:1026-1262")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("I:1000-1025\t1\n"));
+    assert!(stdout.contains("I:1025-1263\t2\n"));
+    assert!(stdout.contains("I:1026-1262\t0\n"));
+
+    // the SNP I:100000 is at the end of ctg:I:1
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("locate")
+        .arg("--all")
+        .arg("--count")
+        .arg("I:99990-100010")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 2);
+    assert!(stdout.contains("I:99990-100010\tctg:I:1\tI:99990-100000\t1\n"));
+    assert!(stdout.contains("I:99990-100010\tctg:I:2\tI:100001-100010\t0\n"));
+
+    Ok(())
+}
+
+#[test]
+fn command_locate_formats() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    // range
+    Command::cargo_bin("gams")?
+        .arg("rg")
+        .arg("tests/S288c/SK1.snp.vcf")
+        .unwrap();
+
+    // locate
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("locate")
+        .arg("--count")
+        .arg("I:1000-2000")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("I:1000-2000\t12"));
+
+    // a SNP on the first base of ctg:I:2
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("locate")
+        .arg("--count")
+        .arg("I:100001-100001")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("I:100001-100001\t1\n"));
+
+    // locate -f
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("locate")
+        .arg("-f")
+        .arg("tests/S288c/I.gff3")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 28);
+    assert!(stdout.contains("I(-):24000-27968\tctg:I:1"));
+    assert!(stdout.contains("I(-):139503-141431\tctg:I:2"));
+
+    Ok(())
+}
+
+#[test]
+fn command_anno() -> anyhow::Result<()> {
+    env_drop_gen()?;
+
+    // anno
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("anno")
+        .arg("tests/S288c/intergenic.json")
+        .arg("tests/S288c/ctg.range.tsv")
+        .arg("-H")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 4);
+    assert_eq!(
+        stdout.lines().next().unwrap().split('\t').count(),
+        8,
+        "field count"
+    );
+    assert!(stdout.contains("85779\t0.0000"));
+    assert!(stdout.contains("130218\t0.1072"));
+
+    // ctgs not in the database
+    let tempdir = TempDir::new().unwrap();
+    let infile = tempdir.path().join("ctg.range.tsv");
+    std::fs::write(&infile, "ctg:II:1\tII:1-1000\nctg:I:1\tI:1-1000\n")?;
+
+    let mut cmd = Command::cargo_bin("gams")?;
+    let output = cmd
+        .arg("anno")
+        .arg("tests/S288c/intergenic.json")
+        .arg(&infile)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(stdout.lines().count(), 2);
+    assert!(stdout.starts_with("ctg:II:1\tII:1-1000\t\n"));
+    assert!(stderr.contains("Unknown ctgs: ctg:II:1"));
 
     Ok(())
 }